@@ -0,0 +1,62 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use csv::StringRecord;
+use csv_payment_processor::{process_transactions, Transaction};
+
+/// Builds a row the same way the CSV reader would, since `Transaction`'s
+/// fields are not constructible from outside the crate
+fn row(tr_type: &str, client: u16, tx: u32, amount: &str) -> Transaction {
+    Transaction::try_from(StringRecord::from(vec![
+        tr_type,
+        &client.to_string(),
+        &tx.to_string(),
+        amount,
+    ]))
+    .expect("bench rows are well-formed")
+}
+
+/// A stream of deposits spread across `clients` accounts
+fn deposit_heavy(rows: u32, clients: u16) -> Vec<Transaction> {
+    (0..rows)
+        .map(|i| row("deposit", (i % clients as u32) as u16 + 1, i + 1, "1.5"))
+        .collect()
+}
+
+/// Deposits followed by a dispute on a share of them and a resolve on half
+/// of those, exercising the reference lookups
+fn dispute_heavy(rows: u32, clients: u16, dispute_every: u32) -> Vec<Transaction> {
+    let mut transactions = deposit_heavy(rows, clients);
+    for i in (0..rows).step_by(dispute_every as usize) {
+        let client = (i % clients as u32) as u16 + 1;
+        transactions.push(row("dispute", client, i + 1, ""));
+        if i % (dispute_every * 2) == 0 {
+            transactions.push(row("resolve", client, i + 1, ""));
+        }
+    }
+    transactions
+}
+
+fn bench_process_transactions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("process_transactions");
+    for clients in [10u16, 1000] {
+        let transactions = deposit_heavy(10_000, clients);
+        group.throughput(Throughput::Elements(transactions.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("deposit_heavy", clients),
+            &transactions,
+            |b, transactions| b.iter(|| process_transactions(transactions)),
+        );
+    }
+    for dispute_every in [2u32, 10] {
+        let transactions = dispute_heavy(10_000, 100, dispute_every);
+        group.throughput(Throughput::Elements(transactions.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("dispute_heavy", dispute_every),
+            &transactions,
+            |b, transactions| b.iter(|| process_transactions(transactions)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_process_transactions);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Feeds arbitrary bytes through the CSV reader and the full processing
+// pipeline. Malformed rows must be skipped or reported, never panic
+fuzz_target!(|data: &[u8]| {
+    let _ = csv_payment_processor::process_reader(data);
+});
@@ -0,0 +1,1068 @@
+pub(crate) const AMOUNT_PRECISION_LIMITER: u16 = 10000;
+
+/// How fractional digits beyond the four-decimal base are folded in when
+/// parsing an amount string
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Drops the extra digits outright
+    Truncate,
+    /// Rounds the fifth digit half away from zero; the historical behavior
+    /// and the default
+    #[default]
+    HalfUp,
+    /// Rounds ties to the nearest even ten-thousandth (banker's rounding)
+    HalfEven,
+}
+
+/// A fixed-point money value with four decimal places of precision
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Amount {
+    whole: i64,
+    decimal: u16,
+}
+
+impl Amount {
+    /// The canonical value in ten-thousandths. The decimal field always holds
+    /// a non-negative offset from `whole * 10000`, so negative amounts are
+    /// represented as e.g. `whole: -11, decimal: 5000` for `-10.5`
+    pub(crate) fn raw_value(&self) -> i64 {
+        // The intermediate product can exceed i64 for values near the limits,
+        // so widen before multiplying; the final value always fits
+        ((self.whole as i128 * AMOUNT_PRECISION_LIMITER as i128) + self.decimal as i128) as i64
+    }
+
+    /// Builds a normalized `Amount` from a canonical ten-thousandths value
+    pub(crate) fn from_raw(raw: i64) -> Self {
+        let base = AMOUNT_PRECISION_LIMITER as i64;
+        Amount {
+            whole: raw.div_euclid(base),
+            decimal: raw.rem_euclid(base) as u16,
+        }
+    }
+
+    /// The value as a signed count of minor units (ten-thousandths), the
+    /// canonical integer form downstream systems exchange; `-10.5` becomes
+    /// `-105000`
+    pub fn to_minor_units(&self) -> i64 {
+        self.raw_value()
+    }
+
+    /// Builds an `Amount` from a signed count of minor units (ten-thousandths),
+    /// the inverse of [`Amount::to_minor_units`]
+    pub fn from_minor_units(n: i64) -> Amount {
+        Amount::from_raw(n)
+    }
+
+    /// The stored whole and decimal components, for callers that need to
+    /// inspect an amount without going through its string form. The decimal
+    /// component is the non-negative ten-thousandths offset from
+    /// `whole * 10000`, so `-10.5` splits as `(-11, 5000)`
+    pub fn parts(&self) -> (i64, u16) {
+        (self.whole, self.decimal)
+    }
+
+    /// The magnitude of the value, so `-10.5` becomes `10.5`
+    pub fn abs(&self) -> Amount {
+        Amount::from_raw(self.raw_value().saturating_abs())
+    }
+
+    /// Subtracts `rhs` but stops at zero instead of going negative, the
+    /// clamping the engine's held-balance and overdraft guards apply to
+    /// their own accumulators; useful for computing dispute deltas
+    pub fn saturating_sub(self, rhs: Amount) -> Amount {
+        Amount::from_raw(self.raw_value().saturating_sub(rhs.raw_value()).max(0))
+    }
+
+    /// Adds two amounts, returning `None` instead of wrapping when the
+    /// canonical value would overflow an `i64`
+    pub fn checked_add(self, rhs: Amount) -> Option<Amount> {
+        self.raw_value()
+            .checked_add(rhs.raw_value())
+            .map(Amount::from_raw)
+    }
+
+    /// Subtracts `rhs`, returning `None` instead of wrapping when the
+    /// canonical value would overflow an `i64`
+    pub fn checked_sub(self, rhs: Amount) -> Option<Amount> {
+        self.raw_value()
+            .checked_sub(rhs.raw_value())
+            .map(Amount::from_raw)
+    }
+
+    /// Whether the value is exactly zero
+    pub fn is_zero(&self) -> bool {
+        self.raw_value() == 0
+    }
+
+    /// Whether the value is strictly below zero
+    pub fn is_negative(&self) -> bool {
+        self.raw_value() < 0
+    }
+
+    /// Whether the value is strictly above zero
+    pub fn is_positive(&self) -> bool {
+        self.raw_value() > 0
+    }
+
+    /// Rounds to `digits` decimal places (at most four), half away from zero,
+    /// matching how the string parser treats the first dropped digit. The
+    /// result still carries the internal four-decimal base
+    pub fn round_to(self, digits: u8) -> Amount {
+        let factor = 10i64.pow(4 - digits.min(4) as u32);
+        let raw = self.raw_value() as i128;
+        let factor_wide = factor as i128;
+        let magnitude = (raw.abs() + factor_wide / 2) / factor_wide * factor_wide;
+        Amount::from_raw((magnitude * raw.signum()) as i64)
+    }
+
+    /// Renders the amount rounded to `digits` decimal places, e.g. `1.0050`
+    /// at two digits becomes `"1.01"` and at zero digits `"1"`
+    pub fn display_with_precision(&self, digits: u8) -> String {
+        let digits = digits.min(4);
+        let raw = self.round_to(digits).raw_value();
+        let base = AMOUNT_PRECISION_LIMITER as i64;
+        let sign = if raw < 0 { "-" } else { "" };
+        if digits == 0 {
+            return format!("{}{}", sign, (raw / base).abs());
+        }
+        let decimal = format!("{:04}", (raw % base).abs());
+        format!(
+            "{}{}.{}",
+            sign,
+            (raw / base).abs(),
+            &decimal[..digits as usize]
+        )
+    }
+
+    /// Renders the amount as a USD-like two-decimal string, rounding half-up
+    /// past the cent, so `12.3456` shows as `"12.35"`. The internal value
+    /// keeps its four-decimal precision
+    pub fn display_cents(&self) -> String {
+        self.display_with_precision(2)
+    }
+
+    /// Renders the amount with the integer part grouped in threes by
+    /// `separator`, so `1234567.89` becomes `1,234,567.8900`. Only the
+    /// integer digits are grouped; the sign and the four decimals are kept
+    /// as-is. Machine-facing output should stay separator-free
+    pub fn display_grouped(&self, separator: char) -> String {
+        let plain = self.to_string();
+        let (integer, fraction) = plain
+            .split_once('.')
+            .expect("Display always renders four decimals");
+        let (sign, digits) = match integer.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", integer),
+        };
+        let mut grouped = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        format!("{}{}.{}", sign, grouped, fraction)
+    }
+
+    /// Divides by `divisor` on the canonical ten-thousandths value, folding
+    /// the lost remainder in per `mode` — the arithmetic footing for
+    /// percentage and interest computations, where the rounding policy
+    /// matters as much as the quotient. Division by zero is reported and
+    /// yields zero rather than panicking
+    pub fn divide_rounding(self, divisor: i64, mode: RoundingMode) -> Amount {
+        if divisor == 0 {
+            log::warn!("Refusing to divide {} by zero; yielding zero", self);
+            return Amount::default();
+        }
+        let raw = self.raw_value() as i128;
+        let divisor_wide = divisor as i128;
+        let quotient = raw / divisor_wide;
+        let remainder = raw % divisor_wide;
+        if remainder == 0 {
+            return Amount::from_raw(quotient as i64);
+        }
+        let twice = 2 * remainder.abs();
+        let round_away = match mode {
+            RoundingMode::Truncate => false,
+            RoundingMode::HalfUp => twice >= divisor_wide.abs(),
+            RoundingMode::HalfEven => {
+                twice > divisor_wide.abs() || (twice == divisor_wide.abs() && quotient % 2 != 0)
+            }
+        };
+        // Rounding away from zero steps in the quotient's direction
+        let step = if (raw < 0) != (divisor < 0) { -1 } else { 1 };
+        Amount::from_raw((quotient + if round_away { step } else { 0 }) as i64)
+    }
+
+    /// Renders the amount with trailing fractional zeros removed, so `10.5000`
+    /// becomes `10.5` and a whole number like `10.0000` becomes just `10`
+    pub fn display_trimmed(&self) -> String {
+        if self.decimal == 0 {
+            return format!("{}", self.whole);
+        }
+        let mut result = format!("{}", self);
+        while result.ends_with('0') {
+            result.pop();
+        }
+        result
+    }
+}
+
+impl core::cmp::Ord for Amount {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.raw_value().cmp(&other.raw_value())
+    }
+}
+
+impl core::cmp::PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::ops::Add for Amount {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Amount::from_raw(self.raw_value() + rhs.raw_value())
+    }
+}
+
+impl std::ops::Sub for Amount {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Amount::from_raw(self.raw_value() - rhs.raw_value())
+    }
+}
+
+impl std::ops::Mul<i64> for Amount {
+    type Output = Self;
+
+    /// Scales the canonical ten-thousandths value, so `1.2500 * 3` is
+    /// exactly `3.7500`; pairs with [`Amount::divide_rounding`] for
+    /// percentage computations
+    fn mul(self, rhs: i64) -> Self::Output {
+        Amount::from_raw(self.raw_value() * rhs)
+    }
+}
+
+impl std::ops::AddAssign for Amount {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::SubAssign for Amount {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl std::ops::Neg for Amount {
+    type Output = Self;
+
+    /// Negates the canonical value, so `-(1.5000)` is `-1.5000` even though
+    /// the normalized split stores it as `whole: -2, decimal: 5000`
+    fn neg(self) -> Self::Output {
+        Amount::from_raw(-self.raw_value())
+    }
+}
+
+/// Why a string could not be parsed as an [`Amount`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum AmountParseError {
+    /// The input was empty, or nothing but a sign
+    Empty,
+    /// The whole part was not a number or does not fit the canonical range
+    InvalidWhole,
+    /// The fractional part contained something other than digits
+    InvalidFraction,
+}
+
+impl std::fmt::Display for AmountParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AmountParseError::Empty => write!(f, "Amount is empty"),
+            AmountParseError::InvalidWhole => write!(f, "Amount has an invalid whole part"),
+            AmountParseError::InvalidFraction => {
+                write!(f, "Amount has an invalid fractional part")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AmountParseError {}
+
+/// Scales a fractional digit string to the four-decimal base, folding the
+/// dropped digits in per `mode`, so under [`RoundingMode::HalfUp`] `"5"`
+/// yields 5000 and `"99999"` yields 10000 (which the caller must carry into
+/// the whole part)
+fn parse_fractional(digits: &str, mode: RoundingMode) -> u16 {
+    let mut digit_vals: Vec<u32> = vec![];
+    for ch in digits.chars() {
+        match ch.to_digit(10) {
+            Some(v) => digit_vals.push(v),
+            None => return 0,
+        }
+    }
+    let mut d: u32 = 0;
+    for i in 0..4 {
+        d = (d * 10) + digit_vals.get(i).copied().unwrap_or(0);
+    }
+    let fifth = digit_vals.get(4).copied().unwrap_or(0);
+    match mode {
+        RoundingMode::Truncate => {}
+        RoundingMode::HalfUp => {
+            if fifth >= 5 {
+                d += 1;
+            }
+        }
+        RoundingMode::HalfEven => {
+            // Anything past the fifth digit breaks the tie upward
+            let past_the_tie = digit_vals.iter().skip(5).any(|v| *v != 0);
+            if fifth > 5 || (fifth == 5 && (past_the_tie || d % 2 == 1)) {
+                d += 1;
+            }
+        }
+    }
+    d as u16
+}
+
+impl Amount {
+    /// Parses a string, folding fractional digits beyond the fourth in per
+    /// `mode`. [`From<&str>`] delegates here with the default half-up
+    pub fn from_str_rounded(value: &str, mode: RoundingMode) -> Amount {
+        Amount::try_from_rounded(value, mode).unwrap_or_default()
+    }
+
+    /// Like [`Amount::from_str_rounded`], but reports malformed input instead
+    /// of defaulting to zero. [`TryFrom<&str>`] delegates here with the
+    /// default half-up
+    pub fn try_from_rounded(value: &str, mode: RoundingMode) -> Result<Amount, AmountParseError> {
+        // One optional sign, `-` or `+`; the latter is normalized away.
+        // Whatever remains must be unsigned so inputs like `--10` or `-+10`
+        // cannot sneak a second sign past the i64 parse below
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value.strip_prefix('+').unwrap_or(value)),
+        };
+        if digits.is_empty() {
+            return Err(AmountParseError::Empty);
+        }
+        if digits.starts_with(['+', '-']) {
+            return Err(AmountParseError::InvalidWhole);
+        }
+        // Exponent notation like `1.5e2`: shift the decimal point textually
+        // so the ordinary path below keeps full precision, then re-parse
+        if let Some(pos) = digits.find(['e', 'E']) {
+            let (mantissa, exponent_part) = digits.split_at(pos);
+            let exponent = exponent_part[1..]
+                .parse::<i32>()
+                .map_err(|_| AmountParseError::InvalidWhole)?;
+            // The mantissa must be numeric before any exponent shortcut may
+            // fire; a tiny exponent on garbage digits is still garbage
+            let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+            if !int_part.chars().all(|ch| ch.is_ascii_digit()) {
+                return Err(AmountParseError::InvalidWhole);
+            }
+            if !frac_part.chars().all(|ch| ch.is_ascii_digit()) {
+                return Err(AmountParseError::InvalidFraction);
+            }
+            let all_digits = format!("{}{}", int_part, frac_part);
+            if all_digits.is_empty() {
+                return Err(AmountParseError::Empty);
+            }
+            // An i64 whole part tops out near 1e15 ten-thousandths; anything
+            // further out is either an overflow or rounds to zero
+            if exponent > 27 {
+                return Err(AmountParseError::InvalidWhole);
+            }
+            if exponent < -27 {
+                return Ok(Amount::default());
+            }
+            let point = int_part.len() as i32 + exponent;
+            let shifted = if point <= 0 {
+                format!("0.{}{}", "0".repeat(-point as usize), all_digits)
+            } else if point as usize >= all_digits.len() {
+                format!(
+                    "{}{}",
+                    all_digits,
+                    "0".repeat(point as usize - all_digits.len())
+                )
+            } else {
+                format!(
+                    "{}.{}",
+                    &all_digits[..point as usize],
+                    &all_digits[point as usize..]
+                )
+            };
+            let parsed = Amount::try_from_rounded(&shifted, mode)?;
+            return Ok(if negative { -parsed } else { parsed });
+        }
+        let mut raw;
+        if let Some((whole, fraction)) = digits.split_once('.') {
+            // A bare fraction like ".5" has an implicit zero whole part
+            let w = if whole.is_empty() {
+                0
+            } else {
+                whole
+                    .parse::<i64>()
+                    .map_err(|_| AmountParseError::InvalidWhole)?
+            };
+            if !fraction.chars().all(|ch| ch.is_ascii_digit()) {
+                return Err(AmountParseError::InvalidFraction);
+            }
+            let d = parse_fractional(fraction, mode);
+            // The scaled value must stay within the canonical i64 range; a
+            // well-formed but astronomically large whole part is an error,
+            // not a wrap or a panic
+            raw = w
+                .checked_mul(AMOUNT_PRECISION_LIMITER as i64)
+                .and_then(|scaled| scaled.checked_add(d as i64))
+                .ok_or(AmountParseError::InvalidWhole)?;
+            // Precision is capped at four decimals, so extra digits are
+            // folded in silently otherwise; say what the input became
+            if fraction.len() > 4 {
+                let mut result = raw;
+                if negative {
+                    result = -result;
+                }
+                log::warn!(
+                    "Amount '{}' exceeds four fractional digits; recorded as {}",
+                    value,
+                    Amount::from_raw(result)
+                );
+            }
+        } else {
+            raw = digits
+                .parse::<i64>()
+                .map_err(|_| AmountParseError::InvalidWhole)?
+                .checked_mul(AMOUNT_PRECISION_LIMITER as i64)
+                .ok_or(AmountParseError::InvalidWhole)?;
+        }
+        if negative {
+            raw = -raw;
+        }
+        Ok(Amount::from_raw(raw))
+    }
+}
+
+impl From<&str> for Amount {
+    /// Lenient parsing: any malformed input collapses to `0.0000`. Callers
+    /// that need to distinguish `"abc"` from zero should use `TryFrom`
+    fn from(value: &str) -> Self {
+        Amount::from_str_rounded(value, RoundingMode::default())
+    }
+}
+
+// `TryFrom<&str>` would be the natural spelling, but the blanket
+// `TryFrom<T> for U where U: From<T>` impl already claims it through the
+// lenient `From<&str>` above, so fallible parsing goes through `FromStr`
+// (`"1.5".parse::<Amount>()`) instead
+impl std::str::FromStr for Amount {
+    type Err = AmountParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Amount::try_from_rounded(value, RoundingMode::default())
+    }
+}
+
+impl From<f64> for Amount {
+    /// Converts a float by scaling to ten-thousandths and rounding half-to-even,
+    /// so digits beyond the fourth decimal place are lost. NaN and infinite
+    /// values become `Amount::default()`
+    fn from(value: f64) -> Self {
+        if !value.is_finite() {
+            return Amount::default();
+        }
+        let scaled = (value * AMOUNT_PRECISION_LIMITER as f64).round_ties_even();
+        if scaled >= i64::MAX as f64 || scaled <= i64::MIN as f64 {
+            return Amount::default();
+        }
+        Amount::from_raw(scaled as i64)
+    }
+}
+
+impl From<i64> for Amount {
+    fn from(value: i64) -> Self {
+        Amount {
+            whole: value,
+            decimal: 0,
+        }
+    }
+}
+
+impl std::fmt::Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let raw = self.raw_value();
+        let base = AMOUNT_PRECISION_LIMITER as i64;
+        let sign = if raw < 0 { "-" } else { "" };
+        write!(
+            f,
+            "{}{}.{:04}",
+            sign,
+            (raw / base).abs(),
+            (raw % base).abs()
+        )
+    }
+}
+
+impl serde::Serialize for Amount {
+    /// Serializes as the four-decimal string form (e.g. `"1.5000"`) rather
+    /// than a float, so precision survives the trip through JSON.
+    ///
+    /// These impls are deliberately not behind a `serde` feature flag: the
+    /// crate's own report writers emit JSON, so serde is a core dependency
+    /// and gating the `Amount` impls would buy nothing
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Amount {
+    /// Deserializes from the same string form through the fallible parser,
+    /// so `"12.3400"` round-trips exactly and malformed input surfaces as a
+    /// deserialization error instead of collapsing to zero
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AmountVisitor;
+
+        impl serde::de::Visitor<'_> for AmountVisitor {
+            type Value = Amount;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "a decimal amount string like \"12.3400\"")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Amount, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(AmountVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compound_assignment_matches_plain_arithmetic() {
+        let mut running = Amount::from("10.5");
+        running += Amount::from("0.25");
+        assert_eq!(running, Amount::from("10.5") + Amount::from("0.25"));
+        running -= Amount::from("11.0");
+        assert_eq!(
+            running,
+            Amount::from("10.5") + Amount::from("0.25") - Amount::from("11.0")
+        );
+    }
+
+    #[test]
+    fn negation_flips_the_canonical_value() {
+        assert_eq!(-Amount::default(), Amount::default());
+        assert_eq!(-Amount::from(3), Amount::from(-3));
+        assert_eq!(-Amount::from("1.5"), Amount::from("-1.5"));
+        assert_eq!(-(-Amount::from("1.5")), Amount::from("1.5"));
+    }
+
+    #[test]
+    fn sign_helpers_track_the_canonical_value() {
+        let positive = Amount::from("0.0001");
+        let negative = Amount::from("-0.0001");
+        let zero = Amount::default();
+        assert!(positive.is_positive() && !positive.is_negative() && !positive.is_zero());
+        assert!(negative.is_negative() && !negative.is_positive() && !negative.is_zero());
+        assert!(zero.is_zero() && !zero.is_positive() && !zero.is_negative());
+    }
+
+    #[test]
+    fn round_to_two_decimals_is_half_away_from_zero() {
+        assert_eq!(Amount::from("1.005").round_to(2), Amount::from("1.01"));
+        assert_eq!(Amount::from("-1.005").round_to(2), Amount::from("-1.01"));
+        assert_eq!(Amount::from("1.0049").round_to(2), Amount::from("1.00"));
+        assert_eq!(Amount::from("1.0050").round_to(4), Amount::from("1.0050"));
+    }
+
+    #[test]
+    fn display_with_precision_cuts_and_rounds() {
+        assert_eq!(Amount::from("1.005").display_with_precision(2), "1.01");
+        assert_eq!(Amount::from("1.2345").display_with_precision(0), "1");
+        assert_eq!(Amount::from("-1.5").display_with_precision(2), "-1.50");
+        assert_eq!(Amount::from("1.5").display_with_precision(4), "1.5000");
+    }
+
+    #[test]
+    fn display_pads_decimal_to_four_digits() {
+        let amount = Amount {
+            whole: 0,
+            decimal: 1,
+        };
+        assert_eq!(amount.to_string(), "0.0001");
+        let amount = Amount {
+            whole: 1,
+            decimal: 5000,
+        };
+        assert_eq!(amount.to_string(), "1.5000");
+        let amount = Amount {
+            whole: 1234,
+            decimal: 50,
+        };
+        assert_eq!(amount.to_string(), "1234.0050");
+    }
+
+    #[test]
+    fn display_trimmed_drops_trailing_zeros() {
+        let amount = Amount {
+            whole: 10,
+            decimal: 5000,
+        };
+        assert_eq!(amount.display_trimmed(), "10.5");
+        let amount = Amount {
+            whole: 10,
+            decimal: 0,
+        };
+        assert_eq!(amount.display_trimmed(), "10");
+        let amount = Amount {
+            whole: 0,
+            decimal: 1,
+        };
+        assert_eq!(amount.display_trimmed(), "0.0001");
+        assert_eq!(Amount::default().display_trimmed(), "0");
+    }
+
+    #[test]
+    fn parse_scales_fractional_digits_to_base() {
+        assert_eq!(
+            Amount::from("1.5"),
+            Amount {
+                whole: 1,
+                decimal: 5000
+            }
+        );
+        assert_eq!(
+            Amount::from("1.25"),
+            Amount {
+                whole: 1,
+                decimal: 2500
+            }
+        );
+        assert_eq!(
+            Amount::from("1.1234"),
+            Amount {
+                whole: 1,
+                decimal: 1234
+            }
+        );
+        assert_eq!(
+            Amount::from("1.123456"),
+            Amount {
+                whole: 1,
+                decimal: 1235
+            }
+        );
+        assert_eq!(
+            Amount::from("1.99999"),
+            Amount {
+                whole: 2,
+                decimal: 0
+            }
+        );
+    }
+
+    #[test]
+    fn rounding_modes_differ_on_the_fifth_digit() {
+        let tie = "1.00005";
+        assert_eq!(
+            Amount::from_str_rounded(tie, RoundingMode::Truncate),
+            Amount::from("1.0000")
+        );
+        assert_eq!(
+            Amount::from_str_rounded(tie, RoundingMode::HalfUp),
+            Amount::from("1.0001")
+        );
+        // 0 ten-thousandths is even, so the tie stays put
+        assert_eq!(
+            Amount::from_str_rounded(tie, RoundingMode::HalfEven),
+            Amount::from("1.0000")
+        );
+        // An odd ten-thousandth rounds the tie up to the even neighbor
+        assert_eq!(
+            Amount::from_str_rounded("1.00015", RoundingMode::HalfEven),
+            Amount::from("1.0002")
+        );
+        // Digits past the tie break it upward regardless of parity
+        assert_eq!(
+            Amount::from_str_rounded("1.000051", RoundingMode::HalfEven),
+            Amount::from("1.0001")
+        );
+        assert_eq!(
+            Amount::from_str_rounded("1.00009", RoundingMode::Truncate),
+            Amount::from("1.0000")
+        );
+    }
+
+    #[test]
+    fn from_f64_rounds_to_four_decimals() {
+        assert_eq!(Amount::from(0.1), Amount::from("0.1000"));
+        assert_eq!(Amount::from(0.0001), Amount::from("0.0001"));
+        assert_eq!(Amount::from(10.555), Amount::from("10.5550"));
+        // 1.00005 has no exact binary representation; the nearest double sits
+        // just above the tie, so it rounds up rather than to even
+        assert_eq!(Amount::from(1.00005), Amount::from("1.0001"));
+        assert_eq!(Amount::from(1.00004), Amount::from("1.0000"));
+        assert_eq!(Amount::from(f64::NAN), Amount::default());
+        assert_eq!(Amount::from(f64::INFINITY), Amount::default());
+    }
+
+    #[test]
+    fn checked_arithmetic_catches_overflow() {
+        let near_max = Amount::from_raw(i64::MAX - 5000);
+        assert!(near_max.checked_add(Amount::from("1.0000")).is_none());
+        assert_eq!(
+            near_max.checked_add(Amount::from("0.0001")),
+            Some(Amount::from_raw(i64::MAX - 4999))
+        );
+        let near_min = Amount::from_raw(i64::MIN + 5000);
+        assert!(near_min.checked_sub(Amount::from("1.0000")).is_none());
+        assert_eq!(
+            near_min.checked_sub(Amount::from("0.0001")),
+            Some(Amount::from_raw(i64::MIN + 4999))
+        );
+    }
+
+    #[test]
+    fn sorting_matches_numeric_order() {
+        let mut amounts = vec![
+            Amount::from("2.0000"),
+            Amount::from("-1.5000"),
+            Amount::from("0.0001"),
+            Amount::from("1.9999"),
+        ];
+        amounts.sort();
+        assert_eq!(
+            amounts,
+            vec![
+                Amount::from("-1.5000"),
+                Amount::from("0.0001"),
+                Amount::from("1.9999"),
+                Amount::from("2.0000"),
+            ]
+        );
+        let mut unique = std::collections::HashSet::new();
+        unique.insert(Amount::from("1.5"));
+        unique.insert(Amount::from("1.5000"));
+        assert_eq!(unique.len(), 1);
+    }
+
+    #[test]
+    fn comparison_follows_numeric_value() {
+        assert!(Amount::from("1.9000") < Amount::from("2.0000"));
+        assert!(Amount::from("2.0001") > Amount::from("2.0000"));
+        assert!(Amount::from("2.0000") >= Amount::from("2.0000"));
+        assert!(Amount::from("2.0000") <= Amount::from("2.0000"));
+        assert!(Amount::from("-1.5000") < Amount::from("-1.0000"));
+        assert!(Amount::from("-0.0001") < Amount::default());
+        assert!(Amount::from("0.0001") > Amount::from("-10.0000"));
+    }
+
+    #[test]
+    fn add_combines_positive_and_negative_amounts() {
+        assert_eq!(
+            Amount::from("-10.50") + Amount::from("10.50"),
+            Amount::default()
+        );
+        assert_eq!(
+            Amount::from("-1.2500") + Amount::from("0.5000"),
+            Amount::from("-0.7500")
+        );
+        assert_eq!(
+            Amount::from("0.7500") + Amount::from("0.7500"),
+            Amount::from("1.5000")
+        );
+    }
+
+    #[test]
+    fn sub_borrows_across_the_decimal_point() {
+        assert_eq!(
+            Amount::from("1.0000") - Amount::from("0.0001"),
+            Amount::from("0.9999")
+        );
+        assert_eq!(
+            Amount::from("5.0001") - Amount::from("1.5000"),
+            Amount::from("3.5001")
+        );
+        assert_eq!(
+            Amount::from("5.0000") - Amount::from("1.5000"),
+            Amount::from("3.5000")
+        );
+    }
+
+    #[test]
+    fn fallible_parsing_reports_malformed_input() {
+        assert_eq!("abc".parse::<Amount>(), Err(AmountParseError::InvalidWhole));
+        assert_eq!(
+            "1.2.3".parse::<Amount>(),
+            Err(AmountParseError::InvalidFraction)
+        );
+        assert_eq!("".parse::<Amount>(), Err(AmountParseError::Empty));
+        assert_eq!("-".parse::<Amount>(), Err(AmountParseError::Empty));
+        assert_eq!("1.5".parse::<Amount>(), Ok(Amount::from("1.5")));
+        assert_eq!(".5".parse::<Amount>(), Ok(Amount::from("0.5")));
+        // The lenient `From` collapses the same inputs to zero
+        assert_eq!(Amount::from("abc"), Amount::default());
+        assert_eq!(Amount::from("1.2.3"), Amount::default());
+        assert_eq!(Amount::from(""), Amount::default());
+    }
+
+    #[test]
+    fn extra_decimal_points_are_invalid() {
+        // The old parser split on '.' and silently read "1.2.3" as 1.2;
+        // everything after the first dot must now be digits
+        assert_eq!(
+            "1.2.3".parse::<Amount>(),
+            Err(AmountParseError::InvalidFraction)
+        );
+        assert_eq!(
+            "1..".parse::<Amount>(),
+            Err(AmountParseError::InvalidFraction)
+        );
+        assert_eq!(
+            "..5".parse::<Amount>(),
+            Err(AmountParseError::InvalidFraction)
+        );
+        assert_eq!(Amount::from("1.2.3"), Amount::default());
+        assert_eq!(Amount::from("1.."), Amount::default());
+    }
+
+    #[test]
+    fn exponent_notation_scales_the_mantissa() {
+        assert_eq!(Amount::from("1.5e2"), Amount::from("150"));
+        assert_eq!(Amount::from("1e-2"), Amount::from("0.01"));
+        assert_eq!(Amount::from("2E3"), Amount::from("2000"));
+        assert_eq!(Amount::from("-1.5e2"), Amount::from("-150"));
+        assert_eq!(Amount::from("12.34e0"), Amount::from("12.34"));
+        // The decimal shift keeps every mantissa digit
+        assert_eq!(Amount::from("1.23456e2"), Amount::from("123.456"));
+        // A malformed exponent is still a parse error, not zero
+        assert_eq!(
+            "1.5e".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+        assert_eq!(
+            "1.5e2.5".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+        // A tiny exponent must not launder a garbage mantissa into zero
+        assert_eq!(
+            "xyze-30".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+        // A numeric mantissa with an exponent below the canonical range
+        // still rounds to zero
+        assert_eq!("1e-30".parse::<Amount>(), Ok(Amount::default()));
+    }
+
+    #[test]
+    fn parts_exposes_the_normalized_components() {
+        assert_eq!(Amount::from("12.3400").parts(), (12, 3400));
+        assert_eq!(Amount::from("0").parts(), (0, 0));
+        // Negative amounts carry the non-negative decimal offset
+        assert_eq!(Amount::from("-10.5").parts(), (-11, 5000));
+    }
+
+    #[test]
+    fn a_leading_plus_sign_is_normalized_away() {
+        assert_eq!(Amount::from("+0"), Amount::from("0"));
+        assert_eq!(Amount::from("+10.50"), Amount::from("10.50"));
+        assert_eq!(Amount::from("+.5"), Amount::from("0.5"));
+        // Exactly one sign is allowed, in either spelling
+        assert_eq!(
+            "++10".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+        assert_eq!(
+            "--10".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+        assert_eq!(
+            "-+10".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+    }
+
+    #[test]
+    fn multiplication_scales_the_canonical_value() {
+        assert_eq!(Amount::from("1.2500") * 3, Amount::from("3.7500"));
+        assert_eq!(Amount::from("-1.2500") * 3, Amount::from("-3.7500"));
+        assert_eq!(Amount::from("2.0") * 1, Amount::from("2.0"));
+    }
+
+    #[test]
+    fn division_follows_the_rounding_mode() {
+        let ten = Amount::from("10.0000");
+        // 10 / 3 leaves a remainder below half, so every mode agrees
+        assert_eq!(
+            ten.divide_rounding(3, RoundingMode::Truncate),
+            Amount::from("3.3333")
+        );
+        assert_eq!(
+            ten.divide_rounding(3, RoundingMode::HalfUp),
+            Amount::from("3.3333")
+        );
+        assert_eq!(
+            ten.divide_rounding(3, RoundingMode::HalfEven),
+            Amount::from("3.3333")
+        );
+        // 1.0001 / 2 lands exactly on a half ten-thousandth, where the
+        // modes diverge
+        let odd = Amount::from("1.0001");
+        assert_eq!(
+            odd.divide_rounding(2, RoundingMode::Truncate),
+            Amount::from("0.5000")
+        );
+        assert_eq!(
+            odd.divide_rounding(2, RoundingMode::HalfUp),
+            Amount::from("0.5001")
+        );
+        assert_eq!(
+            odd.divide_rounding(2, RoundingMode::HalfEven),
+            Amount::from("0.5000")
+        );
+        // The remainder rounds away from zero on the negative side too
+        assert_eq!(
+            Amount::from("-1.0001").divide_rounding(2, RoundingMode::HalfUp),
+            Amount::from("-0.5001")
+        );
+        // Division by zero is refused, not a panic
+        assert_eq!(
+            ten.divide_rounding(0, RoundingMode::HalfUp),
+            Amount::default()
+        );
+    }
+
+    #[test]
+    fn serde_round_trips_the_string_form_exactly() {
+        let amount = Amount::from("12.3400");
+        let serialized = serde_json::to_string(&amount).unwrap();
+        assert_eq!(serialized, "\"12.3400\"");
+        let deserialized: Amount = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, amount);
+        // Negative values survive the trip too
+        let negative = Amount::from("-0.0001");
+        let back: Amount =
+            serde_json::from_str(&serde_json::to_string(&negative).unwrap()).unwrap();
+        assert_eq!(back, negative);
+        // Malformed input is a deserialization error, not zero
+        assert!(serde_json::from_str::<Amount>("\"abc\"").is_err());
+        assert!(serde_json::from_str::<Amount>("1.5").is_err());
+    }
+
+    #[test]
+    fn amounts_past_the_canonical_range_are_errors_not_overflow() {
+        // One past the largest whole part whose scaled value fits an i64
+        assert_eq!(
+            "922337203685478".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+        assert_eq!(
+            "922337203685478.0".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+        // The exponent path scales through the same checked arithmetic
+        assert_eq!(
+            "1e15".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+        // The fractional carry can also tip the scaled value over the edge
+        assert_eq!(
+            "922337203685477.9999".parse::<Amount>(),
+            Err(AmountParseError::InvalidWhole)
+        );
+        // The largest representable whole part still parses
+        assert_eq!(
+            "922337203685477".parse::<Amount>().unwrap().parts(),
+            (922_337_203_685_477, 0)
+        );
+        // The lenient conversion collapses to zero instead of panicking
+        assert_eq!(Amount::from("922337203685478"), Amount::default());
+    }
+
+    #[test]
+    fn grouped_display_inserts_thousands_separators() {
+        assert_eq!(
+            Amount::from("1234567.89").display_grouped(','),
+            "1,234,567.8900"
+        );
+        assert_eq!(
+            Amount::from("-1234567.89").display_grouped(','),
+            "-1,234,567.8900"
+        );
+        // Short integer parts and exact group boundaries stay clean
+        assert_eq!(Amount::from("999.5").display_grouped(','), "999.5000");
+        assert_eq!(Amount::from("1000").display_grouped(','), "1,000.0000");
+        assert_eq!(Amount::from("12.34").display_grouped('.'), "12.3400");
+        // The plain display stays separator-free for machine output
+        assert_eq!(Amount::from("1234567.89").to_string(), "1234567.8900");
+    }
+
+    #[test]
+    fn abs_returns_the_magnitude() {
+        assert_eq!(Amount::from("-10.5").abs(), Amount::from("10.5"));
+        assert_eq!(Amount::from("10.5").abs(), Amount::from("10.5"));
+        assert_eq!(Amount::from("0").abs(), Amount::default());
+        assert_eq!(Amount::from("-0.0001").abs(), Amount::from("0.0001"));
+    }
+
+    #[test]
+    fn saturating_sub_stops_at_zero() {
+        assert_eq!(
+            Amount::from("10.5").saturating_sub(Amount::from("4.5")),
+            Amount::from("6.0")
+        );
+        // An underflowing subtraction clamps to zero instead of going negative
+        assert_eq!(
+            Amount::from("1.0").saturating_sub(Amount::from("2.5")),
+            Amount::default()
+        );
+        assert_eq!(
+            Amount::from("-1.0").saturating_sub(Amount::from("1.0")),
+            Amount::default()
+        );
+    }
+
+    #[test]
+    fn cents_display_rounds_half_up_at_the_third_decimal() {
+        assert_eq!(Amount::from("12.3456").display_cents(), "12.35");
+        // Exactly half a cent rounds away from zero
+        assert_eq!(Amount::from("1.005").display_cents(), "1.01");
+        assert_eq!(Amount::from("1.015").display_cents(), "1.02");
+        assert_eq!(Amount::from("-1.005").display_cents(), "-1.01");
+        assert_eq!(Amount::from("1.0049").display_cents(), "1.00");
+        assert_eq!(Amount::from("10").display_cents(), "10.00");
+    }
+
+    #[test]
+    fn minor_units_round_trip() {
+        assert_eq!(Amount::from("1.5").to_minor_units(), 15000);
+        assert_eq!(Amount::from("-10.5").to_minor_units(), -105000);
+        assert_eq!(Amount::from("0.0001").to_minor_units(), 1);
+        for value in ["1.5", "-10.5", "0.0001", "-0.0001", "42", "-42"] {
+            let amount = Amount::from(value);
+            assert_eq!(Amount::from_minor_units(amount.to_minor_units()), amount);
+        }
+        assert_eq!(Amount::from_minor_units(15000), Amount::from("1.5"));
+        assert_eq!(Amount::from_minor_units(-105000), Amount::from("-10.5"));
+    }
+
+    #[test]
+    fn parse_handles_negative_amounts() {
+        assert_eq!(Amount::from("-10.50").raw_value(), -105000);
+        assert_eq!(Amount::from("-10.50").to_string(), "-10.5000");
+        assert_eq!(Amount::from("-0.0001").raw_value(), -1);
+        assert_eq!(Amount::from("-0.0001").to_string(), "-0.0001");
+        assert_eq!(Amount::from("-100").raw_value(), -1000000);
+        assert_eq!(Amount::from("-100").to_string(), "-100.0000");
+    }
+}
@@ -0,0 +1,43 @@
+//! A small payment engine that replays deposit, withdrawal and dispute
+//! transactions and reports the final state of every client account.
+//!
+//! The binary reads transactions from a CSV file, but the engine itself is
+//! usable programmatically:
+//!
+//! ```
+//! use csv_payment_processor::{process_transactions, Amount, Transaction};
+//!
+//! let records = vec![
+//!     csv::StringRecord::from(vec!["deposit", "1", "1", "5.0"]),
+//!     csv::StringRecord::from(vec!["withdrawal", "1", "2", "1.5"]),
+//! ];
+//! let transactions: Vec<Transaction> = records
+//!     .into_iter()
+//!     .map(|rec| Transaction::try_from(rec).unwrap())
+//!     .collect();
+//! let (statuses, errors) = process_transactions(&transactions);
+//! assert!(errors.is_empty());
+//! assert_eq!(statuses[0].available, Amount::from("3.5"));
+//! ```
+
+mod account;
+mod amount;
+mod process;
+mod report;
+mod transaction;
+
+pub use account::AccountStatus;
+pub use amount::{Amount, AmountParseError, RoundingMode};
+pub use process::{
+    audit_accounts, process_reader, process_transactions, process_transactions_idempotent,
+    process_transactions_streaming, process_transactions_traced, process_transactions_with_context,
+    process_transactions_with_overdraft, process_transactions_with_stats, Ledger, ProcessError,
+    ProcessStats, ProcessingContext,
+};
+pub use report::{
+    summarize, write_json_report, write_jsonl_report, write_report, write_report_with_precision,
+    write_table_report, write_table_report_with_separator, ReportSummary,
+};
+pub use transaction::{
+    ColumnMap, RowError, Transaction, TransactionType, UnknownTransactionType, Validator,
+};
@@ -1,350 +1,624 @@
-use csv::StringRecord;
+use std::io::Read;
 
-extern crate csv;
+use csv_payment_processor::{
+    audit_accounts, process_transactions_traced, process_transactions_with_context, summarize,
+    write_json_report, write_jsonl_report, write_report_with_precision,
+    write_table_report_with_separator, Amount, ColumnMap, Ledger, ProcessingContext, RoundingMode,
+    Transaction, TransactionType, Validator,
+};
 
-const AMOUNT_PRECISION_LIMITER: u16 = 10000;
+/// How many deposit/withdrawal rows the streaming mode remembers for dispute
+/// lookups; disputes against anything older are dropped
+const STREAMING_HISTORY_LIMIT: usize = 1 << 20;
 
-enum TransactionType {
-    Deposit,
-    Withdraw,
-    Dispute,
-    Resolve,
-    Chargeback,
-    Invalid,
+/// How the final report should be rendered
+enum OutputFormat {
+    Csv,
+    Json,
+    JsonLines,
+    Table,
 }
 
-impl From<&str> for TransactionType {
-    fn from(value: &str) -> Self {
-        match value {
-            "deposit" => TransactionType::Deposit,
-            "withdrawal" => TransactionType::Withdraw,
-            "dispute" => TransactionType::Dispute,
-            "resolve" => TransactionType::Resolve,
-            "chargeback" => TransactionType::Chargeback,
-            _ => TransactionType::Invalid,
-        }
-    }
-}
-
-#[derive(Clone, Copy)]
-struct Amount {
-    whole: i64,
-    decimal: u16,
+/// Options gathered from the command line
+struct CliOptions {
+    paths: Vec<String>,
+    delimiter: u8,
+    format: OutputFormat,
+    precision: u8,
+    summary: bool,
+    streaming: bool,
+    validate: bool,
+    strict: bool,
+    rounding: RoundingMode,
+    verbose: bool,
+    clients: Vec<u16>,
+    output: Option<String>,
+    overdraft: Amount,
+    idempotent: bool,
+    has_header: bool,
+    audit: bool,
+    thousands: Option<char>,
+    max_clients: Option<usize>,
+    trace: bool,
+    progress: Option<usize>,
 }
 
-impl core::cmp::PartialEq for Amount {
-    fn eq(&self, other: &Self) -> bool {
-        (self.whole == other.whole) && (self.decimal == other.decimal)
+fn parse_args(args: &[String]) -> Result<CliOptions, String> {
+    let mut options = CliOptions {
+        paths: vec![],
+        delimiter: b',',
+        format: OutputFormat::Csv,
+        precision: 4,
+        summary: false,
+        streaming: false,
+        validate: false,
+        strict: false,
+        rounding: RoundingMode::default(),
+        verbose: false,
+        clients: vec![],
+        output: None,
+        overdraft: Amount::default(),
+        idempotent: false,
+        has_header: true,
+        audit: false,
+        thousands: None,
+        max_clients: None,
+        trace: false,
+        progress: None,
+    };
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--delimiter" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--delimiter requires a value".to_string())?;
+                if value.len() != 1 || !value.is_ascii() {
+                    return Err(format!(
+                        "--delimiter must be a single ASCII character, got '{}'",
+                        value
+                    ));
+                }
+                options.delimiter = value.as_bytes()[0];
+            }
+            "--summary" => options.summary = true,
+            "--streaming" => options.streaming = true,
+            "--validate" => options.validate = true,
+            "--strict" => options.strict = true,
+            "--idempotent" => options.idempotent = true,
+            "--no-header" => options.has_header = false,
+            "--audit" => options.audit = true,
+            "--trace" => options.trace = true,
+            // Feedback for multi-gigabyte runs; --progress-every tunes how
+            // often the count is printed
+            "--progress" => options.progress = Some(options.progress.unwrap_or(100_000)),
+            "--progress-every" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--progress-every requires a row count".to_string())?;
+                options.progress = match value.parse::<usize>() {
+                    Ok(rows) if rows > 0 => Some(rows),
+                    _ => {
+                        return Err(format!(
+                            "--progress-every must be a positive row count, got '{}'",
+                            value
+                        ))
+                    }
+                };
+            }
+            "--thousands" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--thousands requires a separator".to_string())?;
+                let mut chars = value.chars();
+                options.thousands = match (chars.next(), chars.next()) {
+                    (Some(separator), None) => Some(separator),
+                    _ => {
+                        return Err(format!(
+                            "--thousands must be a single character, got '{}'",
+                            value
+                        ))
+                    }
+                };
+            }
+            "--verbose" => options.verbose = true,
+            "--table" => options.format = OutputFormat::Table,
+            "--overdraft" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--overdraft requires a value".to_string())?;
+                options.overdraft = match value.parse::<Amount>() {
+                    Ok(amount) if !amount.is_negative() => amount,
+                    _ => {
+                        return Err(format!(
+                            "--overdraft must be a non-negative amount, got '{}'",
+                            value
+                        ))
+                    }
+                };
+            }
+            "--max-clients" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--max-clients requires a value".to_string())?;
+                options.max_clients = match value.parse::<usize>() {
+                    Ok(limit) if limit > 0 => Some(limit),
+                    _ => {
+                        return Err(format!(
+                            "--max-clients must be a positive count, got '{}'",
+                            value
+                        ))
+                    }
+                };
+            }
+            "--output" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--output requires a path".to_string())?;
+                options.output = Some(value.clone());
+            }
+            // --currency-scale reads better for reports that only care about
+            // e.g. cents; both flags set the same report precision
+            "--precision" | "--currency-scale" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("{} requires a value", arg))?;
+                options.precision = match value.parse::<u8>() {
+                    Ok(digits) if digits <= 4 => digits,
+                    _ => {
+                        return Err(format!(
+                            "{} must be a number from 0 to 4, got '{}'",
+                            arg, value
+                        ))
+                    }
+                };
+            }
+            "--client" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--client requires a value".to_string())?;
+                match value.parse::<u16>() {
+                    Ok(id) => options.clients.push(id),
+                    Err(_) => return Err(format!("--client must be a client ID, got '{}'", value)),
+                }
+            }
+            "--rounding" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--rounding requires a value".to_string())?;
+                options.rounding = match value.as_str() {
+                    "truncate" => RoundingMode::Truncate,
+                    "half-up" => RoundingMode::HalfUp,
+                    "half-even" => RoundingMode::HalfEven,
+                    other => {
+                        return Err(format!(
+                            "--rounding must be 'truncate', 'half-up' or 'half-even', got '{}'",
+                            other
+                        ))
+                    }
+                };
+            }
+            "--format" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--format requires a value".to_string())?;
+                options.format = match value.as_str() {
+                    "csv" => OutputFormat::Csv,
+                    "json" => OutputFormat::Json,
+                    "jsonl" => OutputFormat::JsonLines,
+                    other => {
+                        return Err(format!(
+                            "--format must be 'csv', 'json' or 'jsonl', got '{}'",
+                            other
+                        ))
+                    }
+                };
+            }
+            _ => options.paths.push(arg.clone()),
+        }
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        (self.whole != other.whole) || (self.decimal != other.decimal)
+    // Trace rows come from the batch replay; the streaming path never holds
+    // the full input, so the combination would silently mean something else
+    if options.trace && options.streaming {
+        return Err("--trace is not available with --streaming".to_string());
     }
+    Ok(options)
 }
 
-impl core::cmp::PartialOrd for Amount {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        if self == other {
-            return Some(std::cmp::Ordering::Equal);
-        } else if self < other {
-            return Some(std::cmp::Ordering::Less);
+/// Expands any directory among the sources into its `.csv` files in
+/// lexicographic filename order, so a daily-file archive processes as one
+/// continuous stream. Entries without a `.csv` extension are ignored with a
+/// warning; an unreadable directory fails the run
+fn expand_sources(sources: Vec<String>) -> Option<Vec<String>> {
+    let mut expanded = vec![];
+    for source in sources {
+        if source != "-" && std::path::Path::new(&source).is_dir() {
+            let entries = match std::fs::read_dir(&source) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    log::error!("Could not read directory '{}': {}", source, err);
+                    return None;
+                }
+            };
+            let mut files: Vec<std::path::PathBuf> = vec![];
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "csv") {
+                    files.push(path);
+                } else {
+                    log::warn!("Ignoring non-CSV entry: {}", path.display());
+                }
+            }
+            files.sort();
+            expanded.extend(
+                files
+                    .into_iter()
+                    .map(|path| path.to_string_lossy().into_owned()),
+            );
         } else {
-            return Some(std::cmp::Ordering::Greater);
+            expanded.push(source);
         }
     }
-
-    fn ge(&self, other: &Self) -> bool {
-        self.eq(other)
-            || (self.whole > other.whole)
-            || ((self.whole >= other.whole) && (self.decimal >= other.decimal))
-    }
-
-    fn gt(&self, other: &Self) -> bool {
-        (self.whole > other.whole)
-            || ((self.whole == other.whole) && (self.decimal > other.decimal))
-    }
-
-    fn le(&self, other: &Self) -> bool {
-        self.eq(other)
-            || (self.whole < other.whole)
-            || ((self.whole <= other.whole) && (self.decimal <= other.decimal))
-    }
-
-    fn lt(&self, other: &Self) -> bool {
-        (self.whole < other.whole)
-            || ((self.whole == other.whole) && (self.decimal < other.decimal))
-    }
+    Some(expanded)
 }
 
-impl std::ops::Add for Amount {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        let mut w_add_res = self.whole + rhs.whole;
-        let mut d_add_res = self.decimal + rhs.decimal;
-        if d_add_res >= AMOUNT_PRECISION_LIMITER {
-            w_add_res += (d_add_res / AMOUNT_PRECISION_LIMITER) as i64;
-            d_add_res %= AMOUNT_PRECISION_LIMITER;
-        }
-        Amount {
-            whole: w_add_res,
-            decimal: d_add_res,
-        }
+/// Opens one input source, `-` meaning stdin; unreadable files are reported
+/// with a warning
+fn open_input(path: &str) -> Option<Box<dyn Read>> {
+    match path {
+        "-" => Some(Box::new(std::io::stdin())),
+        path => match std::fs::File::open(path) {
+            Ok(file) => Some(Box::new(file)),
+            Err(_) => {
+                log::error!("Could not create CSV reader for path: {}", path);
+                None
+            }
+        },
     }
 }
 
-impl std::ops::Sub for Amount {
-    type Output = Self;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        let mut w_sub_res = self.whole - rhs.whole;
-        let d_sub_res;
-        if rhs.decimal > self.decimal {
-            w_sub_res -= 1;
-            d_sub_res = rhs.decimal - self.decimal;
-        } else {
-            d_sub_res = self.decimal - rhs.decimal;
-        }
-        Amount {
-            whole: w_sub_res,
-            decimal: d_sub_res,
+/// Parses one already-opened input source into its rows. Each file carries
+/// its own header (unless `--no-header` said otherwise), so the column
+/// layout is resolved per source
+fn transaction_stream(
+    input: Box<dyn Read>,
+    delimiter: u8,
+    rounding: RoundingMode,
+    has_header: bool,
+) -> Box<dyn Iterator<Item = Transaction>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_header)
+        .from_reader(input);
+    // Locate columns by header name when possible, so reordered or extended
+    // files still parse; unrecognized headers and headerless input fall back
+    // to the classic order
+    let columns = if has_header {
+        reader
+            .headers()
+            .ok()
+            .and_then(ColumnMap::from_headers)
+            .unwrap_or_else(ColumnMap::positional)
+    } else {
+        ColumnMap::positional()
+    };
+    Box::new(reader.into_records().filter_map(move |result| {
+        // A record that cannot even be read — a truncated file, a row with
+        // the wrong field count — is reported, not silently dropped; the
+        // csv error carries the offending position
+        let record = match result {
+            Ok(record) => record,
+            Err(err) => {
+                log::warn!("Skipping unreadable record: {}", err);
+                return None;
+            }
+        };
+        match Transaction::from_record_rounded(&record, &columns, rounding) {
+            Ok(transaction) => Some(transaction),
+            Err(err) => {
+                log::warn!("Skipping row: {}", err);
+                None
+            }
         }
-    }
+    }))
 }
 
-impl From<&str> for Amount {
-    fn from(value: &str) -> Self {
-        if value.contains(".") {
-            let splits = value.split(".").collect::<Vec<_>>();
-            let w = splits[0].parse::<i64>().unwrap_or(0);
-            let mut d = splits[1].parse::<u16>().unwrap_or(0);
-            while d >= AMOUNT_PRECISION_LIMITER {
-                d = d / 10;
+/// Like [`transaction_stream`], but for `--strict`: rows that fail to parse
+/// or carry an unrecognized type are recorded as problems instead of being
+/// skipped, so the caller can abort before any balance moves
+fn strict_transaction_stream(
+    input: Box<dyn Read>,
+    delimiter: u8,
+    rounding: RoundingMode,
+    has_header: bool,
+    problems: &mut Vec<String>,
+) -> Vec<Transaction> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_header)
+        .from_reader(input);
+    let columns = if has_header {
+        reader
+            .headers()
+            .ok()
+            .and_then(ColumnMap::from_headers)
+            .unwrap_or_else(ColumnMap::positional)
+    } else {
+        ColumnMap::positional()
+    };
+    let mut transactions = vec![];
+    for result in reader.into_records() {
+        // Strict mode treats an unreadable record like any other problem:
+        // it is listed and the run aborts before any balance moves
+        let record = match result {
+            Ok(record) => record,
+            Err(err) => {
+                problems.push(format!("Unreadable record: {}", err));
+                continue;
             }
-            return Amount {
-                whole: w,
-                decimal: d,
-            };
-        } else {
-            return Amount {
-                whole: value.parse::<i64>().unwrap_or(0),
-                decimal: 0,
-            };
+        };
+        let at = record
+            .position()
+            .map(|p| format!(" at line {}", p.line()))
+            .unwrap_or_default();
+        match Transaction::from_record_rounded(&record, &columns, rounding) {
+            Err(err) => problems.push(err.to_string()),
+            Ok(transaction)
+                if matches!(transaction.transaction_type(), TransactionType::Invalid) =>
+            {
+                problems.push(format!("Row{}: unrecognized transaction type", at));
+            }
+            Ok(transaction) => transactions.push(transaction),
         }
     }
+    transactions
 }
 
-impl From<i64> for Amount {
-    fn from(value: i64) -> Self {
-        Amount {
-            whole: value,
-            decimal: 0,
+fn main() -> std::process::ExitCode {
+    // Errors show by default; RUST_LOG=warn or =info surfaces the per-row
+    // diagnostics the library emits through the `log` facade
+    env_logger::init();
+    let args = std::env::args().collect::<Vec<_>>();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            return std::process::ExitCode::FAILURE;
         }
-    }
-}
-
-impl Default for Amount {
-    fn default() -> Self {
-        Amount {
-            whole: 0,
-            decimal: 0,
+    };
+    // Several paths form one continuous stream, so a dispute in a later file
+    // can reference a deposit from an earlier one. With no path argument the
+    // CSV_INPUT_PATH environment variable is consulted — containerized
+    // deployments mount the file and set the variable — and stdin remains
+    // the last resort
+    let sources = if options.paths.is_empty() {
+        match std::env::var("CSV_INPUT_PATH") {
+            Ok(path) if !path.is_empty() => vec![path],
+            _ => vec!["-".to_string()],
         }
-    }
-}
-
-impl std::fmt::Display for Amount {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}", self.whole, self.decimal)
-    }
-}
-
-struct Transaction {
-    tr_type: TransactionType,
-    client_id: u16,
-    tr_id: u32,
-    amount: Option<Amount>,
-}
-
-impl From<StringRecord> for Transaction {
-    fn from(rec: StringRecord) -> Self {
-        Transaction {
-            tr_type: TransactionType::from(rec.get(0).expect("Invalid Transaction")),
-            client_id: rec
-                .get(1)
-                .expect("Client ID not found")
-                .parse::<u16>()
-                .unwrap_or(0),
-            tr_id: rec
-                .get(2)
-                .expect("Transaction ID not found")
-                .parse::<u32>()
-                .unwrap_or(0),
-            amount: if rec.len() == 4 {
-                Some(Amount::from(rec.get(3).expect("Amount not found")))
-            } else {
-                None
-            },
+    } else {
+        options.paths.clone()
+    };
+    // Directory sources stand for all the .csv files inside them, in
+    // lexicographic filename order
+    let sources = match expand_sources(sources) {
+        Some(sources) => sources,
+        None => return std::process::ExitCode::FAILURE,
+    };
+    let delimiter = options.delimiter;
+    let rounding = options.rounding;
+    let has_header = options.has_header;
+    // Every source is opened before any row is processed, so a typoed path
+    // fails the whole run instead of being silently skipped halfway through
+    let mut inputs: Vec<Box<dyn Read>> = vec![];
+    for path in &sources {
+        match open_input(path) {
+            Some(input) => inputs.push(input),
+            None => return std::process::ExitCode::FAILURE,
         }
     }
-}
-
-struct AccountStatus {
-    client_id: u16,
-    available: Amount,
-    held: Amount,
-    locked: bool,
-}
-
-impl AccountStatus {
-    fn total_amount(&self) -> Amount {
-        self.available + self.held
-    }
-}
-
-impl std::fmt::Display for AccountStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{},        {},     {},   {},  {}",
-            self.client_id,
-            self.available,
-            self.held,
-            self.total_amount(),
-            self.locked
-        )
-    }
-}
-
-fn handle_account(id: u16, statuses: &Vec<AccountStatus>) -> Option<usize> {
-    for (i, r) in statuses.iter().enumerate() {
-        if r.client_id == id {
-            return Some(i);
+    // A dry run: report every structural problem and exit without balances,
+    // so a bad file is caught before a real reconciliation
+    if options.validate {
+        let mut validator = Validator::new();
+        for input in inputs {
+            validator.check_reader(
+                csv::ReaderBuilder::new()
+                    .delimiter(delimiter)
+                    .has_headers(has_header)
+                    .from_reader(input),
+            );
         }
-    }
-    None
-}
-
-fn get_transaction_with_id<'a>(
-    tr_id: u32,
-    transactions: &'a Vec<Transaction>,
-) -> Option<&'a Transaction> {
-    for tr in transactions {
-        if tr.tr_id == tr_id {
-            return Some(tr);
+        for finding in validator.findings() {
+            eprintln!("{}", finding);
         }
+        return if validator.findings().is_empty() {
+            std::process::ExitCode::SUCCESS
+        } else {
+            std::process::ExitCode::FAILURE
+        };
     }
-    None
-}
-
-fn is_disputed_transaction(id: u32, dis: &Vec<u32>) -> bool {
-    dis.iter().position(|&el| -> bool { el == id }).is_some()
-}
-
-fn remove_dispute(id: u32, dis: &mut Vec<u32>) {
-    dis.retain(|&e| e != id);
-}
-
-fn process_transactions<'a>(trs: &'a mut Vec<Transaction>) -> Vec<AccountStatus> {
-    let mut result: Vec<AccountStatus> = vec![];
-    let mut disputes: Vec<u32> = vec![];
-    for (_i, tr) in trs.iter().enumerate() {
-        let index = handle_account(tr.client_id, &result).unwrap_or(result.len());
-        if index == result.len() {
-            result.push(AccountStatus {
-                client_id: tr.client_id,
-                available: Amount::default(),
-                held: Amount::default(),
-                locked: false,
-            });
+    // Strict mode refuses to compute balances from a stream with rows it
+    // cannot understand; a schema change upstream fails loudly instead of
+    // being skipped row by row
+    let parsed_rows: Box<dyn Iterator<Item = Transaction>> = if options.strict {
+        let mut problems = vec![];
+        let mut transactions = vec![];
+        for input in inputs {
+            transactions.extend(strict_transaction_stream(
+                input,
+                delimiter,
+                rounding,
+                has_header,
+                &mut problems,
+            ));
         }
-        let el = result.get_mut(index).expect("No account status found");
-        match tr.tr_type {
-            TransactionType::Deposit => {
-                if !el.locked {
-                    el.available = el.available + tr.amount.expect("No amount found for deposit");
-                }
+        if !problems.is_empty() {
+            for problem in &problems {
+                eprintln!("{}", problem);
             }
-            TransactionType::Withdraw => {
-                if !el.locked {
-                    if (el.available - tr.amount.expect("No amount found for withdrawal"))
-                        >= Amount::default()
-                    {
-                        el.available =
-                            el.available - tr.amount.expect("No amount found for withdrawal");
-                    }
+            return std::process::ExitCode::FAILURE;
+        }
+        Box::new(transactions.into_iter())
+    } else {
+        Box::new(
+            inputs
+                .into_iter()
+                .flat_map(move |input| transaction_stream(input, delimiter, rounding, has_header)),
+        )
+    };
+    // Progress feedback rides the row stream itself, so it works the same
+    // for the batch and streaming paths and stays off stdout
+    let parsed_rows: Box<dyn Iterator<Item = Transaction>> = match options.progress {
+        Some(every) => {
+            let mut seen = 0usize;
+            Box::new(parsed_rows.inspect(move |_| {
+                seen += 1;
+                if seen.is_multiple_of(every) {
+                    eprintln!("Processed {} records", seen);
                 }
+            }))
+        }
+        None => parsed_rows,
+    };
+    // Streaming keeps memory proportional to the dispute history window but
+    // cannot honor a dispute that arrives before its referenced transaction
+    let mut stats = None;
+    let (account_statuses, errors) = if options.streaming {
+        let mut ledger =
+            Ledger::with_history_limit(STREAMING_HISTORY_LIMIT).with_overdraft(options.overdraft);
+        if options.idempotent {
+            ledger = ledger.with_idempotency();
+        }
+        if let Some(limit) = options.max_clients {
+            ledger = ledger.with_client_limit(limit);
+        }
+        for transaction in parsed_rows {
+            ledger.process(transaction);
+        }
+        let errors = ledger.take_errors();
+        (ledger.into_accounts(), errors)
+    } else {
+        let transactions: Vec<Transaction> = parsed_rows.collect();
+        let (statuses, errors, run_stats) = if options.trace {
+            let (statuses, errors, run_stats, trace) = process_transactions_traced(
+                &transactions,
+                options.overdraft,
+                options.idempotent,
+                options.max_clients,
+            );
+            // Trace rows are diagnostics, so like the verbose listings they
+            // go to stderr and leave the report untouched
+            for row in trace {
+                eprintln!("{}", row);
             }
-            TransactionType::Dispute => {
-                if !el.locked {
-                    let candidate_tr = get_transaction_with_id(tr.tr_id, trs);
-                    if candidate_tr.is_some() {
-                        let c_tr = candidate_tr.expect("");
-                        disputes.push(c_tr.tr_id);
-                        let candidate_amount = c_tr.amount.expect("No amount found for dispute");
-                        el.available = el.available - candidate_amount;
-                        el.held = el.held + candidate_amount;
-                    }
-                }
+            (statuses, errors, run_stats)
+        } else {
+            // The context gathers the engine knobs the flags configured
+            let context = ProcessingContext {
+                precision: options.precision,
+                rounding: options.rounding,
+                overdraft: options.overdraft,
+                max_clients: options.max_clients,
+                idempotent: options.idempotent,
+            };
+            process_transactions_with_context(&transactions, &context)
+        };
+        stats = Some(run_stats);
+        (statuses, errors)
+    };
+    for error in &errors {
+        eprintln!("{}", error);
+    }
+    // Processing always considers every row so disputes settle correctly;
+    // the filter only narrows what gets reported
+    let account_statuses: Vec<_> = if options.clients.is_empty() {
+        account_statuses
+    } else {
+        account_statuses
+            .into_iter()
+            .filter(|account| options.clients.contains(&account.client_id))
+            .collect()
+    };
+    // The report goes to the requested file, or to stdout by default;
+    // diagnostics stay on stderr either way
+    let report_out: Box<dyn std::io::Write> = match &options.output {
+        Some(path) => match std::fs::File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(err) => {
+                eprintln!("Could not create output file '{}': {}", path, err);
+                return std::process::ExitCode::FAILURE;
             }
-            TransactionType::Resolve => {
-                if !el.locked {
-                    let candidate_tr = get_transaction_with_id(tr.tr_id, trs);
-                    if candidate_tr.is_some() {
-                        let c_tr = candidate_tr.expect("");
-                        if is_disputed_transaction(c_tr.tr_id, &disputes) {
-                            let candidate_amount =
-                                c_tr.amount.expect("No amount found for resolve");
-                            el.available = el.available + candidate_amount;
-                            el.held = el.held - candidate_amount;
-                            remove_dispute(c_tr.tr_id, &mut disputes);
-                        }
-                    }
-                }
+        },
+        None => Box::new(std::io::stdout()),
+    };
+    match options.format {
+        OutputFormat::Csv => {
+            if let Err(err) =
+                write_report_with_precision(&account_statuses, options.precision, report_out)
+            {
+                eprintln!("Could not write the report: {}", err);
             }
-            TransactionType::Chargeback => {
-                if !el.locked {
-                    let candidate_tr = get_transaction_with_id(tr.tr_id, trs);
-                    if candidate_tr.is_some() {
-                        let c_tr = candidate_tr.expect("");
-                        if is_disputed_transaction(c_tr.tr_id, &disputes) {
-                            let candidate_amount =
-                                c_tr.amount.expect("No amount found for chargeback");
-                            el.held = el.held - candidate_amount;
-                            el.locked = true;
-                            remove_dispute(c_tr.tr_id, &mut disputes);
-                        }
-                    }
-                }
+        }
+        OutputFormat::Json => {
+            if let Err(err) = write_json_report(&account_statuses, report_out) {
+                eprintln!("Could not write the report: {}", err);
             }
-            TransactionType::Invalid => {
-                eprintln!("Invalid transaction found")
+        }
+        OutputFormat::JsonLines => {
+            if let Err(err) = write_jsonl_report(&account_statuses, report_out) {
+                eprintln!("Could not write the report: {}", err);
+            }
+        }
+        OutputFormat::Table => {
+            if let Err(err) =
+                write_table_report_with_separator(&account_statuses, options.thousands, report_out)
+            {
+                eprintln!("Could not write the report: {}", err);
             }
         }
     }
-    result
-}
-
-fn main() {
-    let args = std::env::args().collect::<Vec<_>>();
-    if args.len() > 1 {
-        let mut transactions: Vec<Transaction> = vec![];
-        let csv_reader = csv::Reader::from_path(args[1].as_str());
-        match csv_reader {
-            Ok(mut reader) => {
-                for result in reader.records() {
-                    if result.is_ok() {
-                        transactions.push(Transaction::from(result.unwrap()));
-                    }
-                }
-                let account_statuses = process_transactions(&mut transactions);
-                println!("Client, Available, Held, Total, Locked");
-                for account in account_statuses {
-                    println!("{}", account);
-                }
+    // Dispute listings and the summary go to stderr so stdout stays
+    // machine-parseable
+    if options.verbose {
+        for account in &account_statuses {
+            if let Some(index) = account.last_tx_index {
+                eprintln!(
+                    "Client {} last transacted at input row {}",
+                    account.client_id, index
+                );
+            }
+            if !account.disputed.is_empty() {
+                eprintln!(
+                    "Client {} has open disputes on transactions: {}",
+                    account.client_id,
+                    account
+                        .disputed
+                        .iter()
+                        .map(|id| id.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
             }
-            Err(_) => eprintln!("Could not create CSV reader for path: {}", args[1]),
         }
-    } else {
-        eprintln!("No path for the CSV file provided");
     }
+    // The audit is a tripwire for arithmetic regressions: every held balance
+    // must line up with the account's open disputes
+    if options.audit {
+        let findings = audit_accounts(&account_statuses);
+        for finding in &findings {
+            eprintln!("{}", finding);
+        }
+        if !findings.is_empty() {
+            return std::process::ExitCode::FAILURE;
+        }
+    }
+    if options.summary {
+        eprintln!("{}", summarize(&account_statuses));
+        // Streaming mode does not tally per-type statistics
+        if let Some(stats) = &stats {
+            eprintln!("{}", stats);
+        }
+    }
+    std::process::ExitCode::SUCCESS
 }
@@ -0,0 +1,2647 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::account::AccountStatus;
+use crate::amount::{Amount, AmountParseError, RoundingMode};
+use crate::transaction::{ColumnMap, RowError, Transaction, TransactionType};
+
+/// Whether `client_id` holds an open dispute on `id`. Disputes are keyed by
+/// the (client, transaction) pair, so two clients sharing a coincidental
+/// transaction ID each dispute their own row without interfering — and one
+/// client's row can never settle another client's dispute
+fn is_disputed_by(id: u32, client_id: u16, dis: &HashSet<(u16, u32)>) -> bool {
+    dis.contains(&(client_id, id))
+}
+
+fn remove_dispute(id: u32, client_id: u16, dis: &mut HashSet<(u16, u32)>) {
+    dis.remove(&(client_id, id));
+}
+
+/// Looks up the transaction a dispute-type row refers to in the up-front
+/// index. The index is keyed by the (client, transaction) pair, so another
+/// client's identically-numbered transaction is simply not found here. Only
+/// deposits and withdrawals can be referenced: the index covers every row,
+/// so a dispute whose ID was never minted by a fund row would otherwise
+/// resolve to itself (or another dispute-type row), which carries no amount
+/// to hold funds against
+fn referenced_transaction<'a>(
+    trs: &'a [Transaction],
+    tr_index: &HashMap<(u16, u32), usize>,
+    tr: &Transaction,
+) -> Option<&'a Transaction> {
+    let c_tr = tr_index
+        .get(&(tr.client_id, tr.tr_id))
+        .map(|&idx| &trs[idx])?;
+    if !matches!(
+        c_tr.tr_type,
+        TransactionType::Deposit | TransactionType::Withdraw
+    ) {
+        log::warn!(
+            "Ignoring {} row for client {}: transaction {} is a {} row, not a fund movement",
+            tr.tr_type.as_str(),
+            tr.client_id,
+            tr.tr_id,
+            c_tr.tr_type.as_str()
+        );
+        return None;
+    }
+    Some(c_tr)
+}
+
+/// A per-row failure encountered while replaying transactions. Processing
+/// continues past these rows; the failures are collected and returned
+/// alongside the account statuses
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProcessError {
+    /// A deposit or withdrawal row, or a disputed transaction, carried no amount
+    MissingAmount { tr_id: u32 },
+    /// The row's transaction type was not recognized
+    UnknownTransaction { tr_id: u32 },
+    /// Applying the row would overflow the account balance
+    AmountOverflow { tr_id: u32, client_id: u16 },
+}
+
+impl std::fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessError::MissingAmount { tr_id } => {
+                write!(f, "Transaction {} has no amount", tr_id)
+            }
+            ProcessError::UnknownTransaction { tr_id } => {
+                write!(f, "Transaction {} has an unknown type", tr_id)
+            }
+            ProcessError::AmountOverflow { tr_id, client_id } => {
+                write!(
+                    f,
+                    "Transaction {} would overflow the balance of client {}",
+                    tr_id, client_id
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+/// Counters describing what a processing run saw, for operators who want to
+/// reconcile the report against the input. `skipped` counts rows dropped
+/// before they could apply (idempotent repeats and dispute-type rows whose
+/// reference never resolved); `invalid` counts unrecognized types
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessStats {
+    pub deposits: u64,
+    pub withdrawals: u64,
+    pub transfers: u64,
+    pub disputes: u64,
+    pub resolves: u64,
+    pub chargebacks: u64,
+    pub unlocks: u64,
+    pub invalid: u64,
+    pub skipped: u64,
+}
+
+impl ProcessStats {
+    /// Tallies one recognized row by its type
+    fn count(&mut self, tr_type: &TransactionType) {
+        match tr_type {
+            TransactionType::Deposit => self.deposits += 1,
+            TransactionType::Withdraw => self.withdrawals += 1,
+            TransactionType::Transfer => self.transfers += 1,
+            TransactionType::Dispute => self.disputes += 1,
+            TransactionType::Resolve => self.resolves += 1,
+            TransactionType::Chargeback => self.chargebacks += 1,
+            TransactionType::Unlock => self.unlocks += 1,
+            TransactionType::Invalid => self.invalid += 1,
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "deposits: {}, withdrawals: {}, transfers: {}, disputes: {}, resolves: {}, chargebacks: {}, unlocks: {}, invalid: {}, skipped: {}",
+            self.deposits,
+            self.withdrawals,
+            self.transfers,
+            self.disputes,
+            self.resolves,
+            self.chargebacks,
+            self.unlocks,
+            self.invalid,
+            self.skipped
+        )
+    }
+}
+
+/// Balances accumulated while replaying, kept in `i128` ten-thousandths so
+/// intermediate sums have headroom far beyond `Amount`'s canonical `i64`
+struct WorkingAccount {
+    available: i128,
+    held: i128,
+    locked: bool,
+    tx_count: u64,
+    last_tx_index: Option<usize>,
+}
+
+impl WorkingAccount {
+    /// Converts back to the reported `Amount` form, clamping anything that
+    /// ended up outside the canonical range rather than wrapping
+    fn to_status(&self, client_id: u16, disputes: &HashSet<(u16, u32)>) -> AccountStatus {
+        let clamp =
+            |value: i128| Amount::from_raw(value.clamp(i64::MIN as i128, i64::MAX as i128) as i64);
+        AccountStatus {
+            client_id,
+            available: clamp(self.available),
+            held: clamp(self.held),
+            locked: self.locked,
+            tx_count: self.tx_count,
+            disputed: disputed_ids(client_id, disputes),
+            last_tx_index: self.last_tx_index,
+        }
+    }
+}
+
+/// Fetches the client's working account, opening one on first sight. Only
+/// fund-movement rows (deposits, withdrawals, transfer credits) go through
+/// here; dispute-type rows use [`get_account_mut`] instead. When opening a
+/// new account would push the distinct-client count past `max_clients`, the
+/// account is refused with a warning so crafted inputs cannot grow the map
+/// without bound
+fn get_or_create_account(
+    accounts: &mut HashMap<u16, WorkingAccount>,
+    client_id: u16,
+    max_clients: Option<usize>,
+) -> Option<&mut WorkingAccount> {
+    if !accounts.contains_key(&client_id) {
+        if let Some(limit) = max_clients {
+            if accounts.len() >= limit {
+                log::warn!(
+                    "Refusing to open account for client {}: client limit {} reached",
+                    client_id,
+                    limit
+                );
+                return None;
+            }
+        }
+        log::info!("Opening account for client {}", client_id);
+    }
+    Some(accounts.entry(client_id).or_insert(WorkingAccount {
+        available: 0,
+        held: 0,
+        locked: false,
+        tx_count: 0,
+        last_tx_index: None,
+    }))
+}
+
+/// Fetches the client's working account without creating one. Dispute-type
+/// rows reference prior activity; with no account to dispute against,
+/// creating one would only emit a phantom report row
+fn get_account_mut(
+    accounts: &mut HashMap<u16, WorkingAccount>,
+    client_id: u16,
+) -> Option<&mut WorkingAccount> {
+    accounts.get_mut(&client_id)
+}
+
+/// For idempotent reprocessing: records a fund row's ID in `applied`,
+/// reporting `false` (with a warning) when that ID was already applied so
+/// the caller skips the row. Rows that mint no ID always pass
+fn first_application(applied: &mut HashSet<(u16, u32)>, tr: &Transaction) -> bool {
+    if !matches!(
+        tr.tr_type,
+        TransactionType::Deposit | TransactionType::Withdraw
+    ) {
+        return true;
+    }
+    if applied.insert((tr.client_id, tr.tr_id)) {
+        return true;
+    }
+    log::warn!(
+        "Skipping already-applied transaction {} for client {}",
+        tr.tr_id,
+        tr.client_id
+    );
+    false
+}
+
+/// The per-run knobs every row is applied under: how far `available` may go
+/// negative and how many distinct clients may open accounts
+#[derive(Clone, Copy, Default)]
+struct ReplayLimits {
+    overdraft: i128,
+    max_clients: Option<usize>,
+}
+
+/// Applies a single row to the working accounts. `referenced` must already
+/// be resolved (and client-checked) for dispute-type rows; deposits and
+/// withdrawals ignore it
+fn apply_row(
+    accounts: &mut HashMap<u16, WorkingAccount>,
+    disputes: &mut HashSet<(u16, u32)>,
+    errors: &mut Vec<ProcessError>,
+    tr: &Transaction,
+    referenced: Option<&Transaction>,
+    row_index: usize,
+    limits: ReplayLimits,
+) {
+    if matches!(tr.tr_type, TransactionType::Invalid) {
+        errors.push(ProcessError::UnknownTransaction { tr_id: tr.tr_id });
+        return;
+    }
+    let el = match tr.tr_type {
+        // Deposits and withdrawals open accounts on first sight, unless the
+        // client limit already holds the map at its configured size
+        TransactionType::Deposit | TransactionType::Withdraw => {
+            match get_or_create_account(accounts, tr.client_id, limits.max_clients) {
+                Some(el) => el,
+                None => return,
+            }
+        }
+        // Dispute-type rows must not create accounts; an unknown client is
+        // dropped with a warning instead
+        _ => match get_account_mut(accounts, tr.client_id) {
+            Some(el) => el,
+            None => {
+                log::warn!(
+                    "Ignoring {} row for unknown client {} (tx {})",
+                    tr.tr_type.as_str(),
+                    tr.client_id,
+                    tr.tr_id
+                );
+                return;
+            }
+        },
+    };
+    // A locked account accepts nothing further — except the administrative
+    // unlock itself; say so rather than dropping the row silently, since
+    // the silence makes audits painful
+    if el.locked && !matches!(tr.tr_type, TransactionType::Unlock) {
+        log::warn!(
+            "Skipping transaction {} for client {}: account is locked",
+            tr.tr_id,
+            tr.client_id
+        );
+        return;
+    }
+    // An audit breadcrumb: where in the input this account last saw a row
+    el.last_tx_index = Some(row_index);
+    // Count every deposit/withdrawal row that reached the account, applied
+    // or not, so the column reconciles against the input
+    if matches!(
+        tr.tr_type,
+        TransactionType::Deposit | TransactionType::Withdraw
+    ) {
+        el.tx_count += 1;
+    }
+    match tr.tr_type {
+        TransactionType::Deposit => {
+            let amount = match tr.amount {
+                Some(amount) => amount,
+                None => {
+                    errors.push(ProcessError::MissingAmount { tr_id: tr.tr_id });
+                    return;
+                }
+            };
+            if !amount.is_positive() {
+                log::warn!(
+                    "Rejecting deposit {} for client {}: amount {} is not positive",
+                    tr.tr_id,
+                    tr.client_id,
+                    amount
+                );
+                return;
+            }
+            // Deposits keep the balance within Amount's canonical range,
+            // so converting back for the report can never lose anything
+            let sum = el.available + amount.raw_value() as i128;
+            if i64::try_from(sum).is_ok() {
+                el.available = sum;
+            } else {
+                errors.push(ProcessError::AmountOverflow {
+                    tr_id: tr.tr_id,
+                    client_id: tr.client_id,
+                });
+            }
+        }
+        TransactionType::Withdraw => {
+            let amount = match tr.amount {
+                Some(amount) => amount,
+                None => {
+                    errors.push(ProcessError::MissingAmount { tr_id: tr.tr_id });
+                    return;
+                }
+            };
+            if !amount.is_positive() {
+                log::warn!(
+                    "Rejecting withdrawal {} for client {}: amount {} is not positive",
+                    tr.tr_id,
+                    tr.client_id,
+                    amount
+                );
+                return;
+            }
+            // A withdrawal applies iff the balance after it stays within the
+            // overdraft allowance (zero by default); anything less leaves
+            // the account untouched
+            let amount_raw = amount.raw_value() as i128;
+            if el.available - amount_raw >= -limits.overdraft {
+                el.available -= amount_raw;
+            }
+        }
+        TransactionType::Transfer => {
+            let amount = match tr.amount {
+                Some(amount) => amount,
+                None => {
+                    errors.push(ProcessError::MissingAmount { tr_id: tr.tr_id });
+                    return;
+                }
+            };
+            if !amount.is_positive() {
+                log::warn!(
+                    "Rejecting transfer for client {}: amount {} is not positive",
+                    tr.client_id,
+                    amount
+                );
+                return;
+            }
+            // Transfer rows carry the destination client in the `tx` column
+            let dest_id = match u16::try_from(tr.tr_id) {
+                Ok(dest_id) if dest_id != tr.client_id => dest_id,
+                _ => {
+                    log::warn!(
+                        "Rejecting transfer for client {}: '{}' is not another client",
+                        tr.client_id,
+                        tr.tr_id
+                    );
+                    return;
+                }
+            };
+            // Every check runs before either side moves, so a failed
+            // transfer leaves both accounts exactly as they were
+            let raw = amount.raw_value() as i128;
+            if el.available < raw {
+                log::warn!(
+                    "Ignoring transfer of {} from client {} to client {}: insufficient funds",
+                    amount,
+                    tr.client_id,
+                    dest_id
+                );
+                return;
+            }
+            if accounts.get(&dest_id).is_some_and(|dest| dest.locked) {
+                log::warn!(
+                    "Ignoring transfer of {} from client {} to client {}: destination is locked",
+                    amount,
+                    tr.client_id,
+                    dest_id
+                );
+                return;
+            }
+            // Like deposits, the credit keeps the destination balance within
+            // Amount's canonical range
+            let dest = match get_or_create_account(accounts, dest_id, limits.max_clients) {
+                Some(dest) => dest,
+                None => return,
+            };
+            dest.last_tx_index = Some(row_index);
+            let sum = dest.available + raw;
+            if i64::try_from(sum).is_err() {
+                errors.push(ProcessError::AmountOverflow {
+                    tr_id: tr.tr_id,
+                    client_id: dest_id,
+                });
+                return;
+            }
+            dest.available = sum;
+            if let Some(source) = accounts.get_mut(&tr.client_id) {
+                source.available -= raw;
+            }
+        }
+        TransactionType::Unlock => {
+            if el.locked {
+                log::info!(
+                    "Unlocking account for client {} after manual review",
+                    tr.client_id
+                );
+                el.locked = false;
+            } else {
+                log::warn!(
+                    "Ignoring unlock for client {}: account is not locked",
+                    tr.client_id
+                );
+            }
+        }
+        TransactionType::Dispute => {
+            if let Some(c_tr) = referenced {
+                if is_disputed_by(c_tr.tr_id, tr.client_id, disputes) {
+                    log::warn!(
+                        "Ignoring repeated dispute of transaction {} for client {}",
+                        c_tr.tr_id,
+                        tr.client_id
+                    );
+                } else {
+                    match c_tr.tr_type {
+                        TransactionType::Deposit => {
+                            // A disputed deposit's funds may be clawed back,
+                            // so they move out of the usable balance
+                            let candidate_amount = match c_tr.amount {
+                                Some(amount) => amount,
+                                None => {
+                                    errors.push(ProcessError::MissingAmount { tr_id: c_tr.tr_id });
+                                    return;
+                                }
+                            };
+                            disputes.insert((c_tr.client_id, c_tr.tr_id));
+                            let raw = candidate_amount.raw_value() as i128;
+                            el.available -= raw;
+                            el.held += raw;
+                        }
+                        TransactionType::Withdraw => {
+                            // The withdrawn funds already left the account;
+                            // hold the potential refund until the dispute settles
+                            let candidate_amount = match c_tr.amount {
+                                Some(amount) => amount,
+                                None => {
+                                    errors.push(ProcessError::MissingAmount { tr_id: c_tr.tr_id });
+                                    return;
+                                }
+                            };
+                            disputes.insert((c_tr.client_id, c_tr.tr_id));
+                            el.held += candidate_amount.raw_value() as i128;
+                        }
+                        _ => log::warn!(
+                            "Cannot dispute transaction {}: not a deposit or withdrawal",
+                            c_tr.tr_id
+                        ),
+                    }
+                }
+            }
+        }
+        TransactionType::Resolve => {
+            if let Some(c_tr) = referenced {
+                if is_disputed_by(c_tr.tr_id, tr.client_id, disputes) {
+                    let candidate_amount = match c_tr.amount {
+                        Some(amount) => amount,
+                        None => {
+                            errors.push(ProcessError::MissingAmount { tr_id: c_tr.tr_id });
+                            return;
+                        }
+                    };
+                    // Release at most what is actually held; a shortfall
+                    // (possible after partial operations or corrupted input)
+                    // must not drive `held` negative and corrupt the total
+                    let raw = candidate_amount.raw_value() as i128;
+                    let released = raw.min(el.held.max(0));
+                    if released < raw {
+                        log::warn!(
+                            "Resolving transaction {} for client {}: only {} of {} is held",
+                            c_tr.tr_id,
+                            tr.client_id,
+                            Amount::from_raw(
+                                released.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+                            ),
+                            candidate_amount
+                        );
+                    }
+                    match c_tr.tr_type {
+                        TransactionType::Deposit => {
+                            // The deposit stands; its funds become usable again
+                            el.available += released;
+                            el.held -= released;
+                        }
+                        TransactionType::Withdraw => {
+                            // The withdrawal stands; release the held refund
+                            el.held -= released;
+                        }
+                        _ => {}
+                    }
+                    remove_dispute(c_tr.tr_id, tr.client_id, disputes);
+                } else {
+                    log::warn!(
+                        "Ignoring resolve for client {}: transaction {} is not under dispute",
+                        tr.client_id,
+                        c_tr.tr_id
+                    );
+                }
+            }
+        }
+        TransactionType::Chargeback => {
+            if let Some(c_tr) = referenced {
+                if is_disputed_by(c_tr.tr_id, tr.client_id, disputes) {
+                    let candidate_amount = match c_tr.amount {
+                        Some(amount) => amount,
+                        None => {
+                            errors.push(ProcessError::MissingAmount { tr_id: c_tr.tr_id });
+                            return;
+                        }
+                    };
+                    let disputed_raw = candidate_amount.raw_value() as i128;
+                    // A chargeback row may carry its own amount for a partial
+                    // refund; without one the full disputed amount is charged
+                    let charged_raw = match tr.amount {
+                        Some(partial) => {
+                            let partial_raw = partial.raw_value() as i128;
+                            if !partial.is_positive() || partial_raw > disputed_raw {
+                                log::warn!(
+                                    "Ignoring chargeback for client {}: amount {} is not within the disputed {}",
+                                    tr.client_id,
+                                    partial,
+                                    candidate_amount
+                                );
+                                return;
+                            }
+                            partial_raw
+                        }
+                        None => disputed_raw,
+                    };
+                    match c_tr.tr_type {
+                        TransactionType::Deposit => {
+                            // The charged portion of the deposit leaves the
+                            // account; any remainder becomes usable again
+                            el.held -= disputed_raw;
+                            el.available += disputed_raw - charged_raw;
+                        }
+                        TransactionType::Withdraw => {
+                            // The charged portion of the withdrawal is
+                            // refunded; the rest of the hold is released
+                            el.held -= disputed_raw;
+                            el.available += charged_raw;
+                        }
+                        _ => {}
+                    }
+                    // Only a chargeback of the full disputed amount freezes
+                    // the account
+                    if charged_raw == disputed_raw {
+                        el.locked = true;
+                    }
+                    remove_dispute(c_tr.tr_id, tr.client_id, disputes);
+                } else {
+                    log::warn!(
+                        "Ignoring chargeback for client {}: transaction {} is not under dispute",
+                        tr.client_id,
+                        c_tr.tr_id
+                    );
+                }
+            }
+        }
+        // Reported before the account lookup above
+        TransactionType::Invalid => unreachable!(),
+    }
+}
+
+/// Shared configuration for one processing run. The knobs grew up as
+/// separate function parameters and flags (`overdraft`, `idempotent`,
+/// `max_clients`, the parse-side rounding mode, the report-side precision);
+/// the context gathers them so embedders thread one value instead of five.
+/// Amounts always keep the canonical four-decimal base internally —
+/// `precision` governs how reports render them
+#[derive(Clone, Copy, Debug)]
+pub struct ProcessingContext {
+    /// How many decimal digits reports render (0 through 4)
+    pub precision: u8,
+    /// How parsing folds fractional digits beyond the fourth
+    pub rounding: RoundingMode,
+    /// How far `available` may drop below zero on withdrawals
+    pub overdraft: Amount,
+    /// When set, refuses to open accounts past this many distinct clients
+    pub max_clients: Option<usize>,
+    /// Skips deposit/withdrawal IDs that were already applied
+    pub idempotent: bool,
+}
+
+impl Default for ProcessingContext {
+    /// The historical behavior: full four-decimal precision, half-up
+    /// rounding, no overdraft, unlimited clients, repeats applied as-is
+    fn default() -> ProcessingContext {
+        ProcessingContext {
+            precision: 4,
+            rounding: RoundingMode::default(),
+            overdraft: Amount::default(),
+            max_clients: None,
+            idempotent: false,
+        }
+    }
+}
+
+impl ProcessingContext {
+    /// Parses an amount string under the context's rounding mode
+    pub fn parse_amount(&self, value: &str) -> Result<Amount, AmountParseError> {
+        Amount::try_from_rounded(value, self.rounding)
+    }
+
+    /// Parses a CSV record under the context's rounding mode
+    pub fn parse_record(
+        &self,
+        rec: &csv::StringRecord,
+        columns: &ColumnMap,
+    ) -> Result<Transaction, RowError> {
+        Transaction::from_record_rounded(rec, columns, self.rounding)
+    }
+}
+
+/// Replays the given transactions in order and returns the resulting state of
+/// every account that was touched, along with any per-row failures that were
+/// skipped over
+pub fn process_transactions(trs: &[Transaction]) -> (Vec<AccountStatus>, Vec<ProcessError>) {
+    process_transactions_with_overdraft(trs, Amount::default())
+}
+
+/// Like [`process_transactions`], but also returns the run's
+/// [`ProcessStats`]. `overdraft` and `idempotent` carry the same meaning as
+/// in the dedicated variants; `max_clients`, when set, refuses to open
+/// accounts past that many distinct clients so untrusted input cannot
+/// exhaust memory
+pub fn process_transactions_with_stats(
+    trs: &[Transaction],
+    overdraft: Amount,
+    idempotent: bool,
+    max_clients: Option<usize>,
+) -> (Vec<AccountStatus>, Vec<ProcessError>, ProcessStats) {
+    replay_transactions(trs, overdraft, idempotent, max_clients)
+}
+
+/// Like [`process_transactions`], but lets `available` drop as far as
+/// `-overdraft` on withdrawals, for account types that permit going negative
+/// up to a limit
+pub fn process_transactions_with_overdraft(
+    trs: &[Transaction],
+    overdraft: Amount,
+) -> (Vec<AccountStatus>, Vec<ProcessError>) {
+    let (statuses, errors, _) = replay_transactions(trs, overdraft, false, None);
+    (statuses, errors)
+}
+
+/// Like [`process_transactions_with_stats`], but driven by a
+/// [`ProcessingContext`]; the context's parse-side and report-side fields do
+/// not apply here, since the rows are already parsed and rendering is the
+/// report writers' concern
+pub fn process_transactions_with_context(
+    trs: &[Transaction],
+    context: &ProcessingContext,
+) -> (Vec<AccountStatus>, Vec<ProcessError>, ProcessStats) {
+    replay_transactions(
+        trs,
+        context.overdraft,
+        context.idempotent,
+        context.max_clients,
+    )
+}
+
+/// Like [`process_transactions_with_stats`], but additionally records one
+/// trace line per processed row in the form
+/// `row_index,tr_type,client,tr_id,amount,available_after,held_after,locked`,
+/// showing the touched account's state immediately after the row applied.
+/// Rows that never reach an account (unknown clients, refused openings)
+/// trace zero balances. Backs the binary's `--trace` mode
+pub fn process_transactions_traced(
+    trs: &[Transaction],
+    overdraft: Amount,
+    idempotent: bool,
+    max_clients: Option<usize>,
+) -> (
+    Vec<AccountStatus>,
+    Vec<ProcessError>,
+    ProcessStats,
+    Vec<String>,
+) {
+    let mut trace = vec![];
+    let (statuses, errors, stats) =
+        replay_transactions_traced(trs, overdraft, idempotent, max_clients, Some(&mut trace));
+    (statuses, errors, stats, trace)
+}
+
+/// Like [`process_transactions_with_overdraft`], but skips (with a warning)
+/// any deposit/withdrawal ID that was already applied, so re-feeding
+/// yesterday's file on top of today's cannot double-count
+pub fn process_transactions_idempotent(
+    trs: &[Transaction],
+    overdraft: Amount,
+) -> (Vec<AccountStatus>, Vec<ProcessError>) {
+    let (statuses, errors, _) = replay_transactions(trs, overdraft, true, None);
+    (statuses, errors)
+}
+
+fn replay_transactions(
+    trs: &[Transaction],
+    overdraft: Amount,
+    idempotent: bool,
+    max_clients: Option<usize>,
+) -> (Vec<AccountStatus>, Vec<ProcessError>, ProcessStats) {
+    replay_transactions_traced(trs, overdraft, idempotent, max_clients, None)
+}
+
+fn replay_transactions_traced(
+    trs: &[Transaction],
+    overdraft: Amount,
+    idempotent: bool,
+    max_clients: Option<usize>,
+    mut trace: Option<&mut Vec<String>>,
+) -> (Vec<AccountStatus>, Vec<ProcessError>, ProcessStats) {
+    let mut accounts: HashMap<u16, WorkingAccount> = HashMap::new();
+    let mut disputes: HashSet<(u16, u32)> = HashSet::new();
+    let mut errors: Vec<ProcessError> = vec![];
+    // Index transactions by (client, ID) up front so dispute-type rows can
+    // find their referenced transaction in constant time; two clients may
+    // coincidentally share an ID without interfering. When one client's
+    // input repeats an ID the first occurrence wins, matching the old
+    // linear scan
+    let mut tr_index: HashMap<(u16, u32), usize> = HashMap::new();
+    let mut duplicate_ids: Vec<u32> = vec![];
+    for (i, tr) in trs.iter().enumerate() {
+        match tr_index.entry((tr.client_id, tr.tr_id)) {
+            std::collections::hash_map::Entry::Occupied(seen) => {
+                // Dispute-type rows share the referenced transaction's ID by
+                // design; only deposits and withdrawals mint fresh IDs, so a
+                // repeat between two of those is a genuinely suspect input
+                let both_fund_rows = matches!(
+                    tr.tr_type,
+                    TransactionType::Deposit | TransactionType::Withdraw
+                ) && matches!(
+                    trs[*seen.get()].tr_type,
+                    TransactionType::Deposit | TransactionType::Withdraw
+                );
+                if both_fund_rows {
+                    duplicate_ids.push(tr.tr_id);
+                }
+            }
+            std::collections::hash_map::Entry::Vacant(slot) => {
+                slot.insert(i);
+            }
+        }
+    }
+    duplicate_ids.sort_unstable();
+    duplicate_ids.dedup();
+    if !duplicate_ids.is_empty() {
+        log::warn!(
+            "Input reuses transaction IDs that should be unique: {}",
+            duplicate_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    let mut applied_ids = HashSet::new();
+    let mut stats = ProcessStats::default();
+    for (row_index, tr) in trs.iter().enumerate() {
+        if idempotent && !first_application(&mut applied_ids, tr) {
+            stats.skipped += 1;
+            continue;
+        }
+        stats.count(&tr.tr_type);
+        let referenced = match tr.tr_type {
+            TransactionType::Dispute | TransactionType::Resolve | TransactionType::Chargeback => {
+                let referenced = referenced_transaction(trs, &tr_index, tr);
+                if referenced.is_none() {
+                    stats.skipped += 1;
+                }
+                referenced
+            }
+            _ => None,
+        };
+        apply_row(
+            &mut accounts,
+            &mut disputes,
+            &mut errors,
+            tr,
+            referenced,
+            row_index,
+            ReplayLimits {
+                overdraft: overdraft.raw_value() as i128,
+                max_clients,
+            },
+        );
+        if let Some(out) = trace.as_deref_mut() {
+            let (available, held, locked) = match accounts.get(&tr.client_id) {
+                Some(el) => {
+                    let status = el.to_status(tr.client_id, &disputes);
+                    (status.available, status.held, status.locked)
+                }
+                None => (Amount::default(), Amount::default(), false),
+            };
+            out.push(format!(
+                "{},{},{},{},{},{},{},{}",
+                row_index,
+                tr.tr_type.as_str(),
+                tr.client_id,
+                tr.tr_id,
+                tr.amount
+                    .map(|amount| amount.to_string())
+                    .unwrap_or_default(),
+                available,
+                held,
+                locked
+            ));
+        }
+    }
+    (sorted_statuses(accounts, &disputes), errors, stats)
+}
+
+/// Parses comma-separated rows from any [`std::io::Read`] source and replays
+/// them with [`process_transactions`]. This lets tests and embedders feed an
+/// in-memory byte slice the same way the binary feeds a file or stdin. Rows
+/// that fail to parse are skipped with a warning, mirroring the binary
+pub fn process_reader<R: std::io::Read>(reader: R) -> (Vec<AccountStatus>, Vec<ProcessError>) {
+    let mut reader = csv::Reader::from_reader(reader);
+    let columns = reader
+        .headers()
+        .ok()
+        .and_then(ColumnMap::from_headers)
+        .unwrap_or_else(ColumnMap::positional);
+    let transactions: Vec<Transaction> = reader
+        .into_records()
+        .filter_map(|result| {
+            let record = match result {
+                Ok(record) => record,
+                Err(err) => {
+                    log::warn!("Skipping unreadable record: {}", err);
+                    return None;
+                }
+            };
+            match Transaction::from_record(&record, &columns) {
+                Ok(transaction) => Some(transaction),
+                Err(err) => {
+                    log::warn!("Skipping row: {}", err);
+                    None
+                }
+            }
+        })
+        .collect();
+    process_transactions(&transactions)
+}
+
+/// Replays transactions row-by-row, remembering at most `history_limit`
+/// deposit/withdrawal rows for dispute lookups so memory stays proportional
+/// to the history window rather than the input size. The tradeoffs against
+/// [`process_transactions`]: a dispute cannot reference a row that appears
+/// later in the stream, and once a transaction falls out of the window it
+/// can no longer be disputed
+pub fn process_transactions_streaming<I>(
+    rows: I,
+    history_limit: usize,
+) -> (Vec<AccountStatus>, Vec<ProcessError>)
+where
+    I: IntoIterator<Item = Transaction>,
+{
+    let mut ledger = Ledger::with_history_limit(history_limit);
+    for tr in rows {
+        ledger.process(tr);
+    }
+    let errors = ledger.take_errors();
+    (ledger.into_accounts(), errors)
+}
+
+/// An incrementally-fed engine over the same rules as
+/// [`process_transactions`]. Embedders push rows one at a time and can query
+/// any client's status between rows, which the batch API cannot offer.
+/// Dispute-type rows resolve against the remembered history, so (like the
+/// streaming mode) they can only reference rows already processed
+pub struct Ledger {
+    accounts: HashMap<u16, WorkingAccount>,
+    statuses: HashMap<u16, AccountStatus>,
+    disputes: HashSet<(u16, u32)>,
+    errors: Vec<ProcessError>,
+    history: HashMap<(u16, u32), Transaction>,
+    history_order: VecDeque<(u16, u32)>,
+    history_limit: usize,
+    rows_seen: usize,
+    overdraft: i128,
+    max_clients: Option<usize>,
+    applied_ids: Option<HashSet<(u16, u32)>>,
+}
+
+impl Ledger {
+    /// A ledger that remembers every deposit/withdrawal for dispute lookups
+    pub fn new() -> Ledger {
+        Ledger::with_history_limit(usize::MAX)
+    }
+
+    /// A ledger that remembers at most `history_limit` deposit/withdrawal
+    /// rows; disputes against evicted rows are dropped
+    pub fn with_history_limit(history_limit: usize) -> Ledger {
+        Ledger {
+            accounts: HashMap::new(),
+            statuses: HashMap::new(),
+            disputes: HashSet::new(),
+            errors: vec![],
+            history: HashMap::new(),
+            history_order: VecDeque::new(),
+            history_limit,
+            rows_seen: 0,
+            overdraft: 0,
+            max_clients: None,
+            applied_ids: None,
+        }
+    }
+
+    /// Skips (with a warning) any deposit/withdrawal ID that was already
+    /// applied, mirroring [`process_transactions_idempotent`] for callers
+    /// that re-feed overlapping inputs
+    pub fn with_idempotency(mut self) -> Ledger {
+        self.applied_ids = Some(HashSet::new());
+        self
+    }
+
+    /// Permits withdrawals to take `available` as low as `-overdraft`,
+    /// mirroring [`process_transactions_with_overdraft`] for the batch API
+    pub fn with_overdraft(mut self, overdraft: Amount) -> Ledger {
+        self.overdraft = overdraft.raw_value() as i128;
+        self
+    }
+
+    /// Refuses (with a warning) to open accounts past `max_clients` distinct
+    /// clients, bounding memory against inputs that spray client IDs
+    pub fn with_client_limit(mut self, max_clients: usize) -> Ledger {
+        self.max_clients = Some(max_clients);
+        self
+    }
+
+    /// Applies one transaction to the ledger
+    pub fn process(&mut self, tr: Transaction) {
+        if let Some(applied_ids) = &mut self.applied_ids {
+            if !first_application(applied_ids, &tr) {
+                self.rows_seen += 1;
+                return;
+            }
+        }
+        let referenced = match tr.tr_type {
+            TransactionType::Dispute | TransactionType::Resolve | TransactionType::Chargeback => {
+                self.history.get(&(tr.client_id, tr.tr_id))
+            }
+            _ => None,
+        };
+        apply_row(
+            &mut self.accounts,
+            &mut self.disputes,
+            &mut self.errors,
+            &tr,
+            referenced,
+            self.rows_seen,
+            ReplayLimits {
+                overdraft: self.overdraft,
+                max_clients: self.max_clients,
+            },
+        );
+        self.rows_seen += 1;
+        // Refresh the queryable snapshot for the touched client; a transfer
+        // also touches the destination client named in its `tx` column
+        if let Some(account) = self.accounts.get(&tr.client_id) {
+            self.statuses.insert(
+                tr.client_id,
+                account.to_status(tr.client_id, &self.disputes),
+            );
+        }
+        if matches!(tr.tr_type, TransactionType::Transfer) {
+            if let Ok(dest_id) = u16::try_from(tr.tr_id) {
+                if let Some(account) = self.accounts.get(&dest_id) {
+                    self.statuses
+                        .insert(dest_id, account.to_status(dest_id, &self.disputes));
+                }
+            }
+        }
+        if matches!(
+            tr.tr_type,
+            TransactionType::Deposit | TransactionType::Withdraw
+        ) {
+            let key = (tr.client_id, tr.tr_id);
+            match self.history.entry(key) {
+                std::collections::hash_map::Entry::Occupied(_) => {
+                    log::warn!(
+                        "Input reuses a transaction ID that should be unique: {}",
+                        key.1
+                    );
+                }
+                std::collections::hash_map::Entry::Vacant(slot) => {
+                    slot.insert(tr);
+                    self.history_order.push_back(key);
+                }
+            }
+            if self.history.len() > self.history_limit {
+                if let Some(evicted) = self.history_order.pop_front() {
+                    self.history.remove(&evicted);
+                }
+            }
+        }
+    }
+
+    /// The current status of a client, if any row has touched that account
+    pub fn account(&self, client_id: u16) -> Option<&AccountStatus> {
+        self.statuses.get(&client_id)
+    }
+
+    /// Drains the row-level failures collected so far
+    pub fn take_errors(&mut self) -> Vec<ProcessError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Consumes the ledger, returning every account sorted by client
+    pub fn into_accounts(self) -> Vec<AccountStatus> {
+        let mut statuses: Vec<AccountStatus> = self.statuses.into_values().collect();
+        statuses.sort_by_key(|status| status.client_id);
+        statuses
+    }
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Ledger::new()
+    }
+}
+
+/// The still-open dispute IDs belonging to one client, sorted for
+/// deterministic listings
+fn disputed_ids(client_id: u16, disputes: &HashSet<(u16, u32)>) -> Vec<u32> {
+    let mut ids: Vec<u32> = disputes
+        .iter()
+        .filter(|(owner, _)| *owner == client_id)
+        .map(|(_, id)| *id)
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// Checks the engine's internal invariants on a finished report: held funds
+/// are never negative, and a held balance is always backed by at least one
+/// open dispute (and vice versa). Returns one finding per violation; an
+/// empty result means the run is consistent. Backs the binary's `--audit`
+/// mode as a tripwire for arithmetic regressions
+pub fn audit_accounts(accounts: &[AccountStatus]) -> Vec<String> {
+    let mut findings = vec![];
+    for account in accounts {
+        if account.held.is_negative() {
+            findings.push(format!(
+                "Client {}: held balance {} is negative",
+                account.client_id, account.held
+            ));
+        }
+        if account.held.is_positive() && account.disputed.is_empty() {
+            findings.push(format!(
+                "Client {}: held balance {} has no open dispute backing it",
+                account.client_id, account.held
+            ));
+        }
+        if account.held.is_zero() && !account.disputed.is_empty() {
+            findings.push(format!(
+                "Client {}: open disputes on transactions {} but nothing is held",
+                account.client_id,
+                account
+                    .disputed
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+    findings
+}
+
+/// HashMap iteration order is arbitrary, so sort by client for deterministic,
+/// diff-friendly reports
+fn sorted_statuses(
+    accounts: HashMap<u16, WorkingAccount>,
+    disputes: &HashSet<(u16, u32)>,
+) -> Vec<AccountStatus> {
+    let mut statuses: Vec<AccountStatus> = accounts
+        .into_iter()
+        .map(|(client_id, account)| account.to_status(client_id, disputes))
+        .collect();
+    statuses.sort_by_key(|status| status.client_id);
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn withdrawal_dispute_fixture(last: TransactionType) -> Vec<Transaction> {
+        vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("100.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Withdraw,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("30.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 2,
+                amount: None,
+            },
+            Transaction {
+                tr_type: last,
+                client_id: 1,
+                tr_id: 2,
+                amount: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn process_reader_accepts_in_memory_bytes() {
+        let input: &[u8] = b"type,client,tx,amount\n\
+            deposit,1,1,5.0\n\
+            withdrawal,1,2,1.5\n\
+            not-a-row\n";
+        let (statuses, errors) = process_reader(input);
+        assert!(errors.is_empty());
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].available, Amount::from("3.5000"));
+        assert_eq!(statuses[0].tx_count, 2);
+    }
+
+    #[test]
+    fn empty_amount_column_is_an_error_not_a_zero_deposit() {
+        // Four fields with nothing after the last comma must not record a
+        // zero-amount deposit; the row is skipped and reported instead
+        let input: &[u8] = b"type,client,tx,amount\n\
+            deposit,1,1,\n\
+            deposit,1,2,3.0\n";
+        let (statuses, errors) = process_reader(input);
+        assert_eq!(errors, vec![ProcessError::MissingAmount { tr_id: 1 }]);
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].available, Amount::from("3.0000"));
+    }
+
+    #[test]
+    fn garbage_amount_column_is_skipped_not_a_zero_deposit() {
+        // The sibling of the empty-amount case: a present but unparseable
+        // amount fails the whole row, so it neither records a zero deposit
+        // nor bumps the account's transaction count
+        let input: &[u8] = b"type,client,tx,amount\n\
+            deposit,1,1,abc\n\
+            deposit,1,2,3.0\n";
+        let (statuses, errors) = process_reader(input);
+        assert!(errors.is_empty());
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].available, Amount::from("3.0000"));
+        assert_eq!(statuses[0].tx_count, 1);
+    }
+
+    #[test]
+    fn bom_prefixed_input_parses_like_plain_input() {
+        let input: &[u8] = b"\xef\xbb\xbftype,client,tx,amount\ndeposit,1,1,2.5\n";
+        let (statuses, errors) = process_reader(input);
+        assert!(errors.is_empty());
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].available, Amount::from("2.5000"));
+    }
+
+    /// Collects every logged message so tests can assert on diagnostics.
+    /// `log::set_logger` is process-global, so every test that needs it
+    /// installs it through [`install_capture_logger`] and only the first
+    /// call takes effect
+    struct CaptureLogger;
+
+    fn install_capture_logger() {
+        let _ = log::set_logger(&CaptureLogger);
+        log::set_max_level(log::LevelFilter::Warn);
+    }
+
+    static CAPTURED: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            CAPTURED.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn skipped_transactions_warn_through_the_log_facade() {
+        install_capture_logger();
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 901,
+                tr_id: 90001,
+                amount: Some(Amount::from("-1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 902,
+                tr_id: 90002,
+                amount: None,
+            },
+        ];
+        process_transactions(&transactions);
+        let captured = CAPTURED.lock().unwrap();
+        assert!(captured.iter().any(|message| message
+            == "Rejecting deposit 90001 for client 901: amount -1.0000 is not positive"));
+        assert!(captured
+            .iter()
+            .any(|message| message == "Ignoring dispute row for unknown client 902 (tx 90002)"));
+    }
+
+    #[test]
+    fn overlong_amount_fractions_warn_when_parsed() {
+        install_capture_logger();
+        let parsed = Amount::from("1.123456");
+        assert_eq!(parsed, Amount::from("1.1235"));
+        let _ = Amount::from("1.1234");
+        let captured = CAPTURED.lock().unwrap();
+        assert!(captured.iter().any(|message| message
+            == "Amount '1.123456' exceeds four fractional digits; recorded as 1.1235"));
+        // Four digits fit exactly, so nothing is reported for that parse
+        assert!(!captured
+            .iter()
+            .any(|message| message.starts_with("Amount '1.1234'")));
+    }
+
+    #[test]
+    fn garbage_client_rows_never_open_a_phantom_account() {
+        // A non-numeric client field is a row error; it must not default to
+        // client 0 and merge unrelated rows into one account
+        let input = "type,client,tx,amount\n\
+                     deposit,oops,1,5.0\n\
+                     deposit,1,2,2.0\n";
+        let (statuses, errors) = process_reader(input.as_bytes());
+        assert!(errors.is_empty());
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].client_id, 1);
+        assert_eq!(statuses[0].available, Amount::from("2.0"));
+    }
+
+    #[test]
+    fn stats_count_rows_by_type() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("3.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Withdraw,
+                client_id: 1,
+                tr_id: 3,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Resolve,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            // References another client's deposit, so it cannot apply
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 2,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Invalid,
+                client_id: 1,
+                tr_id: 4,
+                amount: None,
+            },
+        ];
+        let (_, _, stats) =
+            process_transactions_with_stats(&transactions, Amount::default(), false, None);
+        assert_eq!(
+            stats,
+            ProcessStats {
+                deposits: 2,
+                withdrawals: 1,
+                transfers: 0,
+                disputes: 2,
+                resolves: 1,
+                chargebacks: 0,
+                unlocks: 0,
+                invalid: 1,
+                skipped: 1,
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "deposits: 2, withdrawals: 1, transfers: 0, disputes: 2, resolves: 1, chargebacks: 0, unlocks: 0, invalid: 1, skipped: 1"
+        );
+    }
+
+    #[test]
+    fn idempotent_mode_applies_a_repeated_deposit_id_once() {
+        // The same file fed twice: every ID repeats
+        let day = || {
+            vec![
+                Transaction {
+                    tr_type: TransactionType::Deposit,
+                    client_id: 1,
+                    tr_id: 1,
+                    amount: Some(Amount::from("10.0000")),
+                },
+                Transaction {
+                    tr_type: TransactionType::Withdraw,
+                    client_id: 1,
+                    tr_id: 2,
+                    amount: Some(Amount::from("4.0000")),
+                },
+            ]
+        };
+        let mut twice = day();
+        twice.extend(day());
+        let (statuses, errors) = process_transactions_idempotent(&twice, Amount::default());
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("6.0000"));
+        assert_eq!(statuses[0].tx_count, 2);
+        // Without the mode the repeats double-count
+        let (statuses, _) = process_transactions(&twice);
+        assert_eq!(statuses[0].available, Amount::from("12.0000"));
+    }
+
+    #[test]
+    fn overdraft_allows_withdrawals_down_to_the_limit() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0000")),
+            },
+            // Lands exactly at -5.0, the allowance boundary
+            Transaction {
+                tr_type: TransactionType::Withdraw,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("15.0000")),
+            },
+        ];
+        let (statuses, errors) =
+            process_transactions_with_overdraft(&transactions, Amount::from("5.0000"));
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("-5.0000"));
+    }
+
+    #[test]
+    fn overdraft_rejects_one_cent_past_the_limit() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Withdraw,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("15.0100")),
+            },
+        ];
+        let (statuses, errors) =
+            process_transactions_with_overdraft(&transactions, Amount::from("5.0000"));
+        assert!(errors.is_empty());
+        // The withdrawal is skipped outright; nothing is partially applied
+        assert_eq!(statuses[0].available, Amount::from("10.0000"));
+    }
+
+    #[test]
+    fn transactions_built_in_code_process_without_any_csv() {
+        let transactions = vec![
+            Transaction::new(
+                TransactionType::Deposit,
+                1,
+                1,
+                Some(Amount::from("10.0000")),
+            ),
+            Transaction::new(
+                TransactionType::Withdraw,
+                1,
+                2,
+                Some(Amount::from("2.5000")),
+            ),
+            Transaction::new(TransactionType::Dispute, 1, 1, None),
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("-2.5000"));
+        assert_eq!(statuses[0].held, Amount::from("10.0000"));
+        assert_eq!(statuses[0].disputed, vec![1]);
+    }
+
+    #[test]
+    fn audit_passes_a_clean_run_and_flags_corrupt_statuses() {
+        // A run with an open dispute and a settled one: held matches the
+        // open dispute exactly, so the audit stays quiet
+        let input = "type,client,tx,amount\n\
+                     deposit,1,1,10.0\n\
+                     deposit,2,2,5.0\n\
+                     dispute,1,1,\n\
+                     dispute,2,2,\n\
+                     resolve,2,2,\n";
+        let (statuses, errors) = process_reader(input.as_bytes());
+        assert!(errors.is_empty());
+        assert!(audit_accounts(&statuses).is_empty());
+        // A hand-corrupted status trips the tripwire
+        let corrupt = vec![AccountStatus {
+            client_id: 3,
+            available: Amount::from("1.0"),
+            held: Amount::from("2.0"),
+            locked: false,
+            tx_count: 1,
+            disputed: vec![],
+            last_tx_index: None,
+        }];
+        assert_eq!(
+            audit_accounts(&corrupt),
+            vec!["Client 3: held balance 2.0000 has no open dispute backing it".to_string()]
+        );
+    }
+
+    #[test]
+    fn malformed_transaction_ids_never_become_transaction_zero() {
+        // The bad deposit row is skipped entirely; a dispute against tx 0
+        // finds nothing to claw back
+        let input = "type,client,tx,amount\n\
+                     deposit,1,oops,5.0\n\
+                     deposit,1,1,2.0\n\
+                     dispute,1,0,\n";
+        let (statuses, errors) = process_reader(input.as_bytes());
+        assert!(errors.is_empty());
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].available, Amount::from("2.0"));
+        assert_eq!(statuses[0].held, Amount::from("0"));
+        assert!(statuses[0].disputed.is_empty());
+    }
+
+    #[test]
+    fn resolve_never_drives_held_negative() {
+        // An account that somehow holds less than the disputed deposit, e.g.
+        // after partial operations or corrupted input
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            1u16,
+            WorkingAccount {
+                available: 0,
+                held: 40000,
+                locked: false,
+                tx_count: 1,
+                last_tx_index: None,
+            },
+        );
+        let mut disputes = HashSet::new();
+        disputes.insert((1u16, 1u32));
+        let mut errors = vec![];
+        let deposit = Transaction {
+            tr_type: TransactionType::Deposit,
+            client_id: 1,
+            tr_id: 1,
+            amount: Some(Amount::from("10.0000")),
+        };
+        let resolve = Transaction {
+            tr_type: TransactionType::Resolve,
+            client_id: 1,
+            tr_id: 1,
+            amount: None,
+        };
+        apply_row(
+            &mut accounts,
+            &mut disputes,
+            &mut errors,
+            &resolve,
+            Some(&deposit),
+            0,
+            ReplayLimits::default(),
+        );
+        let account = &accounts[&1];
+        // Only the 4.0 that was actually held is released
+        assert_eq!(account.held, 0);
+        assert_eq!(account.available, 40000);
+        assert!(errors.is_empty());
+        assert!(disputes.is_empty());
+    }
+
+    #[test]
+    fn last_tx_index_tracks_the_latest_row_per_account() {
+        // Client 1 transacts at rows 0 and 5; client 2 fills the rows between
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 2,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 3,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 4,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 5,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Withdraw,
+                client_id: 1,
+                tr_id: 6,
+                amount: Some(Amount::from("0.5000")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].client_id, 1);
+        assert_eq!(statuses[0].last_tx_index, Some(5));
+        assert_eq!(statuses[1].client_id, 2);
+        assert_eq!(statuses[1].last_tx_index, Some(4));
+    }
+
+    #[test]
+    fn client_limit_stops_opening_new_accounts() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("1.0")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 2,
+                amount: Some(Amount::from("2.0")),
+            },
+            // Past the limit: no account is opened and nothing is recorded
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 3,
+                tr_id: 3,
+                amount: Some(Amount::from("3.0")),
+            },
+            // An already-open account still accepts rows under the limit
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 4,
+                amount: Some(Amount::from("0.5")),
+            },
+        ];
+        let (statuses, errors, _) =
+            process_transactions_with_stats(&transactions, Amount::default(), false, Some(2));
+        assert!(errors.is_empty());
+        assert_eq!(
+            statuses.iter().map(|s| s.client_id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(statuses[0].available, Amount::from("1.5000"));
+    }
+
+    #[test]
+    fn trace_reports_running_balances_per_row() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("5.0")),
+            },
+            Transaction {
+                tr_type: TransactionType::Withdraw,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("1.5")),
+            },
+        ];
+        let (_, errors, _, trace) =
+            process_transactions_traced(&transactions, Amount::default(), false, None);
+        assert!(errors.is_empty());
+        assert_eq!(
+            trace,
+            vec![
+                "0,deposit,1,1,5.0000,5.0000,0.0000,false",
+                "1,withdrawal,1,2,1.5000,3.5000,0.0000,false",
+            ]
+        );
+    }
+
+    #[test]
+    fn context_gathers_the_run_configuration() {
+        let context = ProcessingContext {
+            precision: 2,
+            rounding: RoundingMode::Truncate,
+            overdraft: Amount::from("5.0"),
+            ..Default::default()
+        };
+        // Parsing follows the context's rounding mode
+        assert_eq!(
+            context.parse_amount("1.23456").unwrap(),
+            Amount::from("1.2345")
+        );
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0")),
+            },
+            Transaction {
+                tr_type: TransactionType::Withdraw,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("12.0")),
+            },
+        ];
+        let (statuses, errors, _) = process_transactions_with_context(&transactions, &context);
+        assert!(errors.is_empty());
+        // The withdrawal applies because the context permits the overdraft
+        assert_eq!(statuses[0].available, Amount::from("-2.0000"));
+        // The context's precision drives how reports render the balance
+        assert_eq!(
+            statuses[0]
+                .available
+                .display_with_precision(context.precision),
+            "-2.00"
+        );
+    }
+
+    #[test]
+    fn a_lone_dispute_cannot_reference_itself() {
+        // Transaction 7 was never minted by a deposit or withdrawal, so the
+        // index resolves the dispute's ID to the dispute row itself; the row
+        // must be skipped, not held against a phantom amount
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("5.0")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 7,
+                amount: None,
+            },
+        ];
+        let (statuses, errors, stats) =
+            process_transactions_with_stats(&transactions, Amount::default(), false, None);
+        assert!(errors.is_empty());
+        assert_eq!(stats.skipped, 1);
+        assert_eq!(statuses[0].available, Amount::from("5.0000"));
+        assert_eq!(statuses[0].held, Amount::default());
+        assert!(statuses[0].disputed.is_empty());
+    }
+
+    #[test]
+    fn unlock_reopens_an_account_after_a_chargeback() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("5.0")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Chargeback,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            // Locked: this deposit is refused
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("3.0")),
+            },
+            Transaction {
+                tr_type: TransactionType::Unlock,
+                client_id: 1,
+                tr_id: 0,
+                amount: None,
+            },
+            // After the unlock, deposits apply again
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 3,
+                amount: Some(Amount::from("2.0")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert!(!statuses[0].locked);
+        assert_eq!(statuses[0].available, Amount::from("2.0000"));
+    }
+
+    #[test]
+    fn total_clamps_when_available_and_held_jointly_overflow() {
+        // Each balance fits the canonical range on its own; a disputed
+        // maximum deposit plus a second maximum deposit must clamp the
+        // reported total instead of wrapping
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("922337203685477")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("922337203685477")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("922337203685477"));
+        assert_eq!(statuses[0].held, Amount::from("922337203685477"));
+        assert_eq!(
+            statuses[0].total_amount(),
+            Amount::from_minor_units(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn dispute_rows_do_not_create_accounts() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("5.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 2,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Resolve,
+                client_id: 3,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Chargeback,
+                client_id: 4,
+                tr_id: 1,
+                amount: None,
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        // Only the deposit opened an account; the dispute-type rows against
+        // unknown clients left no phantom report rows behind
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].client_id, 1);
+        assert_eq!(statuses[0].available, Amount::from("5.0000"));
+    }
+
+    fn disputed_deposit_with_chargeback(amount: Option<Amount>) -> Vec<Transaction> {
+        vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Chargeback,
+                client_id: 1,
+                tr_id: 1,
+                amount,
+            },
+        ]
+    }
+
+    #[test]
+    fn full_chargeback_drops_the_hold_and_locks() {
+        let transactions = disputed_deposit_with_chargeback(None);
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].available, Amount::default());
+        assert_eq!(statuses[0].held, Amount::default());
+        assert!(statuses[0].locked);
+    }
+
+    #[test]
+    fn partial_chargeback_returns_the_remainder_without_locking() {
+        let transactions = disputed_deposit_with_chargeback(Some(Amount::from("4.0000")));
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].available, Amount::from("6.0000"));
+        assert_eq!(statuses[0].held, Amount::default());
+        assert!(!statuses[0].locked);
+        assert!(statuses[0].disputed.is_empty());
+    }
+
+    #[test]
+    fn explicit_full_chargeback_still_locks() {
+        let transactions = disputed_deposit_with_chargeback(Some(Amount::from("10.0000")));
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].available, Amount::default());
+        assert!(statuses[0].locked);
+    }
+
+    #[test]
+    fn chargeback_larger_than_the_dispute_is_ignored() {
+        let transactions = disputed_deposit_with_chargeback(Some(Amount::from("11.0000")));
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].held, Amount::from("10.0000"));
+        assert!(!statuses[0].locked);
+        // The dispute stays open for a corrected chargeback
+        assert_eq!(statuses[0].disputed, vec![1]);
+    }
+
+    #[test]
+    fn resolve_from_another_client_leaves_the_dispute_open() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("5.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 2,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            // Client 2 tries to settle client 1's dispute
+            Transaction {
+                tr_type: TransactionType::Resolve,
+                client_id: 2,
+                tr_id: 1,
+                amount: None,
+            },
+        ];
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].held, Amount::from("5.0000"));
+        assert_eq!(statuses[0].disputed, vec![1]);
+        assert_eq!(statuses[1].available, Amount::from("1.0000"));
+    }
+
+    #[test]
+    fn statuses_list_open_disputes_per_account() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("2.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 3,
+                amount: Some(Amount::from("3.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 2,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Resolve,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+        ];
+        let (statuses, _) = process_transactions(&transactions);
+        // Only the unresolved dispute remains listed, and only on its owner
+        assert_eq!(statuses[0].disputed, vec![2]);
+        assert!(statuses[1].disputed.is_empty());
+    }
+
+    #[test]
+    fn transfer_moves_funds_between_clients() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0000")),
+            },
+            // The `tx` column names the destination client
+            Transaction {
+                tr_type: TransactionType::Transfer,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("4.0000")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].available, Amount::from("6.0000"));
+        assert_eq!(statuses[1].client_id, 2);
+        assert_eq!(statuses[1].available, Amount::from("4.0000"));
+    }
+
+    #[test]
+    fn transfer_without_funds_leaves_both_accounts_untouched() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Transfer,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("5.0000")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        // The destination account is never opened by a failed transfer
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].available, Amount::from("1.0000"));
+    }
+
+    #[test]
+    fn transfer_to_a_locked_account_is_ignored() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 2,
+                amount: Some(Amount::from("3.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 2,
+                tr_id: 2,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Chargeback,
+                client_id: 2,
+                tr_id: 2,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Transfer,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("4.0000")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("10.0000"));
+        assert_eq!(statuses[1].available, Amount::default());
+        assert!(statuses[1].locked);
+    }
+
+    #[test]
+    fn transfer_from_a_locked_account_is_ignored() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Chargeback,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Transfer,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("4.0000")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].available, Amount::default());
+        assert!(statuses[0].locked);
+    }
+
+    #[test]
+    fn disputed_withdrawal_resolve_releases_the_hold() {
+        let transactions = withdrawal_dispute_fixture(TransactionType::Resolve);
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].available, Amount::from("70.0000"));
+        assert_eq!(statuses[0].held, Amount::default());
+        assert!(!statuses[0].locked);
+    }
+
+    #[test]
+    fn disputed_withdrawal_chargeback_refunds_and_locks() {
+        let transactions = withdrawal_dispute_fixture(TransactionType::Chargeback);
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].available, Amount::from("100.0000"));
+        assert_eq!(statuses[0].held, Amount::default());
+        assert!(statuses[0].locked);
+    }
+
+    #[test]
+    fn many_disputes_and_resolves_stay_consistent() {
+        let mut transactions: Vec<Transaction> = (0..100)
+            .map(|i| Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: i,
+                amount: Some(Amount::from("1.0000")),
+            })
+            .collect();
+        for i in 0..100 {
+            transactions.push(Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: i,
+                amount: None,
+            });
+        }
+        for i in 0..50 {
+            transactions.push(Transaction {
+                tr_type: TransactionType::Resolve,
+                client_id: 1,
+                tr_id: i,
+                amount: None,
+            });
+        }
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].available, Amount::from("50.0000"));
+        assert_eq!(statuses[0].held, Amount::from("50.0000"));
+    }
+
+    #[test]
+    fn dispute_finds_its_transaction_in_a_large_batch() {
+        let mut transactions: Vec<Transaction> = (0..1000)
+            .map(|i| Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: (i % 50) as u16,
+                tr_id: i,
+                amount: Some(Amount::from("1.0000")),
+            })
+            .collect();
+        transactions.push(Transaction {
+            tr_type: TransactionType::Dispute,
+            client_id: 7,
+            tr_id: 7,
+            amount: None,
+        });
+        let (statuses, _) = process_transactions(&transactions);
+        let disputed = statuses.iter().find(|s| s.client_id == 7).unwrap();
+        assert_eq!(disputed.held, Amount::from("1.0000"));
+        assert_eq!(disputed.available, Amount::from("19.0000"));
+    }
+
+    #[test]
+    fn repeated_dispute_is_a_no_op() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("25.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+        ];
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].held, Amount::from("25.0000"));
+        assert_eq!(statuses[0].available, Amount::default());
+    }
+
+    fn deposit_then(last: TransactionType) -> Vec<Transaction> {
+        vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("20.0000")),
+            },
+            Transaction {
+                tr_type: last,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn tx_count_tracks_deposits_and_withdrawals() {
+        let transactions: Vec<Transaction> = (0..3)
+            .map(|i| Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: i,
+                amount: Some(Amount::from("1.0000")),
+            })
+            .collect();
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses[0].tx_count, 3);
+    }
+
+    #[test]
+    fn resolve_without_a_prior_dispute_changes_nothing() {
+        let (statuses, errors) = process_transactions(&deposit_then(TransactionType::Resolve));
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("20.0000"));
+        assert_eq!(statuses[0].held, Amount::default());
+    }
+
+    #[test]
+    fn chargeback_without_a_prior_dispute_changes_nothing() {
+        let (statuses, errors) = process_transactions(&deposit_then(TransactionType::Chargeback));
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("20.0000"));
+        assert_eq!(statuses[0].held, Amount::default());
+        assert!(!statuses[0].locked);
+    }
+
+    #[test]
+    fn ledger_answers_queries_mid_stream() {
+        let mut ledger = Ledger::new();
+        ledger.process(Transaction {
+            tr_type: TransactionType::Deposit,
+            client_id: 1,
+            tr_id: 1,
+            amount: Some(Amount::from("10.0000")),
+        });
+        assert_eq!(
+            ledger.account(1).map(|status| status.available),
+            Some(Amount::from("10.0000"))
+        );
+        assert!(ledger.account(2).is_none());
+        ledger.process(Transaction {
+            tr_type: TransactionType::Dispute,
+            client_id: 1,
+            tr_id: 1,
+            amount: None,
+        });
+        let status = ledger.account(1).unwrap();
+        assert_eq!(status.available, Amount::default());
+        assert_eq!(status.held, Amount::from("10.0000"));
+        ledger.process(Transaction {
+            tr_type: TransactionType::Deposit,
+            client_id: 2,
+            tr_id: 2,
+            amount: Some(Amount::from("1.0000")),
+        });
+        assert!(ledger.take_errors().is_empty());
+        let accounts = ledger.into_accounts();
+        let clients = accounts.iter().map(|s| s.client_id).collect::<Vec<_>>();
+        assert_eq!(clients, vec![1, 2]);
+    }
+
+    #[test]
+    fn streaming_matches_batch_on_a_dispute_fixture() {
+        let batch = process_transactions(&withdrawal_dispute_fixture(TransactionType::Chargeback));
+        let streamed = process_transactions_streaming(
+            withdrawal_dispute_fixture(TransactionType::Chargeback),
+            16,
+        );
+        assert_eq!(batch.1, streamed.1);
+        assert_eq!(batch.0.len(), streamed.0.len());
+        for (batch_status, streamed_status) in batch.0.iter().zip(streamed.0.iter()) {
+            assert_eq!(batch_status.client_id, streamed_status.client_id);
+            assert_eq!(batch_status.available, streamed_status.available);
+            assert_eq!(batch_status.held, streamed_status.held);
+            assert_eq!(batch_status.locked, streamed_status.locked);
+        }
+    }
+
+    #[test]
+    fn streaming_drops_disputes_outside_the_history_window() {
+        let mut transactions: Vec<Transaction> = (0..10)
+            .map(|i| Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: i,
+                amount: Some(Amount::from("1.0000")),
+            })
+            .collect();
+        // Transaction 0 was evicted from a window of 4, so this dispute is lost
+        transactions.push(Transaction {
+            tr_type: TransactionType::Dispute,
+            client_id: 1,
+            tr_id: 0,
+            amount: None,
+        });
+        let (statuses, errors) = process_transactions_streaming(transactions, 4);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("10.0000"));
+        assert_eq!(statuses[0].held, Amount::default());
+    }
+
+    #[test]
+    fn dispute_matching_another_dispute_row_is_skipped() {
+        // Transaction 9 only ever appears on dispute rows, so the second
+        // dispute resolves the ID to an amount-less dispute row; that must be
+        // skipped, not panic
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("5.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 9,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 9,
+                amount: None,
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("5.0000"));
+        assert_eq!(statuses[0].held, Amount::default());
+    }
+
+    #[test]
+    fn duplicate_deposit_ids_are_still_applied() {
+        // The warning lists id 5; both rows still move funds so totals match
+        // what actually happened
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 5,
+                amount: Some(Amount::from("1.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 5,
+                amount: Some(Amount::from("2.0000")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("3.0000"));
+    }
+
+    #[test]
+    fn summing_a_billion_units_does_not_overflow() {
+        let transactions: Vec<Transaction> = (0..1000)
+            .map(|i| Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: i,
+                amount: Some(Amount::from(1_000_000)),
+            })
+            .collect();
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from(1_000_000_000));
+    }
+
+    #[test]
+    fn deposit_after_chargeback_lock_is_skipped() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("40.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Chargeback,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            // The account is now locked, so this deposit must not land
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("5.0000")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::default());
+        assert_eq!(statuses[0].held, Amount::default());
+        assert!(statuses[0].locked);
+    }
+
+    #[test]
+    fn clients_sharing_a_transaction_id_dispute_their_own_rows() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("5.0")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 1,
+                amount: Some(Amount::from("7.0")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 2,
+                tr_id: 1,
+                amount: None,
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        // Each client's dispute holds that client's own deposit
+        assert_eq!(statuses[0].held, Amount::from("5.0000"));
+        assert_eq!(statuses[0].available, Amount::default());
+        assert_eq!(statuses[1].held, Amount::from("7.0000"));
+        assert_eq!(statuses[1].available, Amount::default());
+        assert_eq!(statuses[0].disputed, vec![1]);
+        assert_eq!(statuses[1].disputed, vec![1]);
+    }
+
+    #[test]
+    fn cross_client_dispute_moves_no_funds() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("50.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 2,
+                tr_id: 2,
+                amount: Some(Amount::from("10.0000")),
+            },
+            // Client 2 attempts to dispute client 1's deposit
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 2,
+                tr_id: 1,
+                amount: None,
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("50.0000"));
+        assert_eq!(statuses[0].held, Amount::default());
+        assert_eq!(statuses[1].available, Amount::from("10.0000"));
+        assert_eq!(statuses[1].held, Amount::default());
+    }
+
+    #[test]
+    fn dispute_for_an_unknown_client_creates_no_account() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("5.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Dispute,
+                client_id: 2,
+                tr_id: 1,
+                amount: None,
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        let clients = statuses.iter().map(|s| s.client_id).collect::<Vec<_>>();
+        assert_eq!(clients, vec![1]);
+    }
+
+    #[test]
+    fn statuses_are_sorted_by_client_id() {
+        let transactions = [3u16, 1, 2]
+            .iter()
+            .enumerate()
+            .map(|(i, &client_id)| Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id,
+                tr_id: i as u32,
+                amount: Some(Amount::from("1.0000")),
+            })
+            .collect::<Vec<_>>();
+        let (statuses, _) = process_transactions(&transactions);
+        let clients = statuses.iter().map(|s| s.client_id).collect::<Vec<_>>();
+        assert_eq!(clients, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn deposit_without_amount_is_reported_not_fatal() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: None,
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("5.0000")),
+            },
+        ];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert_eq!(errors, vec![ProcessError::MissingAmount { tr_id: 1 }]);
+        assert_eq!(statuses[0].available, Amount::from("5.0000"));
+    }
+
+    fn deposit_then_withdrawal(amount: &str) -> Vec<Transaction> {
+        vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Withdraw,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from(amount)),
+            },
+        ]
+    }
+
+    #[test]
+    fn withdrawal_of_the_exact_balance_empties_the_account() {
+        let (statuses, errors) = process_transactions(&deposit_then_withdrawal("10.0000"));
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::default());
+    }
+
+    #[test]
+    fn withdrawal_one_cent_over_the_balance_is_refused() {
+        let (statuses, _) = process_transactions(&deposit_then_withdrawal("10.0100"));
+        assert_eq!(statuses[0].available, Amount::from("10.0000"));
+    }
+
+    #[test]
+    fn withdrawal_one_ten_thousandth_over_the_balance_is_refused() {
+        let (statuses, errors) = process_transactions(&deposit_then_withdrawal("10.0001"));
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::from("10.0000"));
+    }
+
+    #[test]
+    fn withdrawal_from_a_zero_balance_is_refused() {
+        let transactions = vec![Transaction {
+            tr_type: TransactionType::Withdraw,
+            client_id: 1,
+            tr_id: 1,
+            amount: Some(Amount::from("0.0001")),
+        }];
+        let (statuses, errors) = process_transactions(&transactions);
+        assert!(errors.is_empty());
+        assert_eq!(statuses[0].available, Amount::default());
+        assert_eq!(statuses[0].tx_count, 1);
+    }
+
+    #[test]
+    fn withdrawal_of_zero_changes_nothing() {
+        let (statuses, _) = process_transactions(&deposit_then_withdrawal("0.0000"));
+        assert_eq!(statuses[0].available, Amount::from("10.0000"));
+    }
+
+    #[test]
+    fn negative_amount_rows_are_rejected() {
+        let transactions = vec![
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 1,
+                amount: Some(Amount::from("10.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Deposit,
+                client_id: 1,
+                tr_id: 2,
+                amount: Some(Amount::from("-50.0000")),
+            },
+            Transaction {
+                tr_type: TransactionType::Withdraw,
+                client_id: 1,
+                tr_id: 3,
+                amount: Some(Amount::from("-5.0000")),
+            },
+        ];
+        let (statuses, _) = process_transactions(&transactions);
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].available, Amount::from("10.0000"));
+    }
+}
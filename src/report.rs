@@ -0,0 +1,342 @@
+use crate::account::AccountStatus;
+use crate::amount::Amount;
+
+/// Aggregate totals across every reported account, for the `--summary` flag
+pub struct ReportSummary {
+    pub clients: usize,
+    pub total_available: Amount,
+    pub total_held: Amount,
+    pub locked_accounts: usize,
+}
+
+/// Computes the aggregate totals of a finished report. Sums are accumulated
+/// in `i128` ten-thousandths and clamped to `Amount`'s range, mirroring how
+/// processing itself accumulates
+pub fn summarize(accounts: &[AccountStatus]) -> ReportSummary {
+    let mut total_available: i128 = 0;
+    let mut total_held: i128 = 0;
+    let mut locked_accounts = 0;
+    for account in accounts {
+        total_available += account.available.raw_value() as i128;
+        total_held += account.held.raw_value() as i128;
+        if account.locked {
+            locked_accounts += 1;
+        }
+    }
+    let clamp =
+        |value: i128| Amount::from_raw(value.clamp(i64::MIN as i128, i64::MAX as i128) as i64);
+    ReportSummary {
+        clients: accounts.len(),
+        total_available: clamp(total_available),
+        total_held: clamp(total_held),
+        locked_accounts,
+    }
+}
+
+impl std::fmt::Display for ReportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "clients: {}, total available: {}, total held: {}, locked accounts: {}",
+            self.clients, self.total_available, self.total_held, self.locked_accounts
+        )
+    }
+}
+
+/// Writes the account report as CSV with a `client,available,held,total,locked`
+/// header, so downstream tools can parse the output directly. Amounts carry
+/// the full four decimal places
+pub fn write_report<W: std::io::Write>(
+    accounts: &[AccountStatus],
+    out: W,
+) -> Result<(), csv::Error> {
+    write_report_with_precision(accounts, 4, out)
+}
+
+/// Like [`write_report`], but rounds amounts to `precision` decimal places
+/// (0–4) for currencies that use fewer than four
+pub fn write_report_with_precision<W: std::io::Write>(
+    accounts: &[AccountStatus],
+    precision: u8,
+    out: W,
+) -> Result<(), csv::Error> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(["client", "available", "held", "total", "locked", "tx_count"])?;
+    for account in accounts {
+        writer.write_record(&[
+            account.client_id.to_string(),
+            account.available.display_with_precision(precision),
+            account.held.display_with_precision(precision),
+            account.total_amount().display_with_precision(precision),
+            account.locked.to_string(),
+            account.tx_count.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes a human-readable table with column widths computed from the data,
+/// so a wide amount and a narrow one still line up. The legacy `Display` on
+/// [`AccountStatus`] pads with a fixed number of spaces and drifts once
+/// values differ in width; CSV output is unaffected either way
+pub fn write_table_report<W: std::io::Write>(
+    accounts: &[AccountStatus],
+    out: W,
+) -> std::io::Result<()> {
+    write_table_report_with_separator(accounts, None, out)
+}
+
+/// Like [`write_table_report`], but groups the integer digits of every
+/// amount with `separator` (e.g. `1,234,567.8900`) when one is given. The
+/// machine-facing CSV and JSON writers never apply a separator
+pub fn write_table_report_with_separator<W: std::io::Write>(
+    accounts: &[AccountStatus],
+    separator: Option<char>,
+    mut out: W,
+) -> std::io::Result<()> {
+    let amount = |value: crate::amount::Amount| match separator {
+        Some(separator) => value.display_grouped(separator),
+        None => value.to_string(),
+    };
+    let headers = ["client", "available", "held", "total", "locked", "tx_count"];
+    let rows: Vec<[String; 6]> = accounts
+        .iter()
+        .map(|account| {
+            [
+                account.client_id.to_string(),
+                amount(account.available),
+                amount(account.held),
+                amount(account.total_amount()),
+                account.locked.to_string(),
+                account.tx_count.to_string(),
+            ]
+        })
+        .collect();
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    let render = |cells: [&str; 6]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:>width$}", cell))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+    writeln!(out, "{}", render(headers))?;
+    for row in &rows {
+        let cells = [
+            row[0].as_str(),
+            row[1].as_str(),
+            row[2].as_str(),
+            row[3].as_str(),
+            row[4].as_str(),
+            row[5].as_str(),
+        ];
+        writeln!(out, "{}", render(cells))?;
+    }
+    Ok(())
+}
+
+/// Writes the account report as a JSON array of objects mirroring the CSV
+/// columns, for callers that feed the output to web services
+pub fn write_json_report<W: std::io::Write>(
+    accounts: &[AccountStatus],
+    out: W,
+) -> Result<(), serde_json::Error> {
+    serde_json::to_writer_pretty(out, accounts)
+}
+
+/// Writes the account report as newline-delimited JSON: one compact object
+/// per account per line, with the same fields as [`write_json_report`].
+/// Log shippers and `jq` pipelines consume this without buffering an array
+pub fn write_jsonl_report<W: std::io::Write>(
+    accounts: &[AccountStatus],
+    mut out: W,
+) -> std::io::Result<()> {
+    for account in accounts {
+        serde_json::to_writer(&mut out, account)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_totals_add_up() {
+        let accounts = vec![
+            AccountStatus {
+                client_id: 1,
+                available: Amount::from("1.5000"),
+                held: Amount::from("0.5000"),
+                locked: false,
+                tx_count: 1,
+                disputed: vec![],
+                last_tx_index: None,
+            },
+            AccountStatus {
+                client_id: 2,
+                available: Amount::from("2.0000"),
+                held: Amount::from("0.2500"),
+                locked: true,
+                tx_count: 2,
+                disputed: vec![],
+                last_tx_index: None,
+            },
+        ];
+        let summary = summarize(&accounts);
+        assert_eq!(summary.clients, 2);
+        assert_eq!(summary.total_available, Amount::from("3.5000"));
+        assert_eq!(summary.total_held, Amount::from("0.7500"));
+        assert_eq!(summary.locked_accounts, 1);
+        assert_eq!(
+            summary.to_string(),
+            "clients: 2, total available: 3.5000, total held: 0.7500, locked accounts: 1"
+        );
+    }
+
+    #[test]
+    fn report_round_trips_through_a_csv_reader() {
+        let accounts = vec![AccountStatus {
+            client_id: 1,
+            available: Amount::from("1.5000"),
+            held: Amount::from("0.2500"),
+            locked: false,
+            tx_count: 3,
+            disputed: vec![],
+            last_tx_index: None,
+        }];
+        let mut out: Vec<u8> = vec![];
+        write_report(&accounts, &mut out).unwrap();
+        let mut reader = csv::Reader::from_reader(out.as_slice());
+        assert_eq!(
+            reader.headers().unwrap(),
+            &csv::StringRecord::from(vec![
+                "client",
+                "available",
+                "held",
+                "total",
+                "locked",
+                "tx_count"
+            ])
+        );
+        let records = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0],
+            csv::StringRecord::from(vec!["1", "1.5000", "0.2500", "1.7500", "false", "3"])
+        );
+    }
+
+    #[test]
+    fn table_columns_align_across_wide_and_narrow_amounts() {
+        let accounts = vec![
+            AccountStatus {
+                client_id: 1,
+                available: Amount::from("12345.6789"),
+                held: Amount::from("0.2500"),
+                locked: false,
+                tx_count: 42,
+                disputed: vec![],
+                last_tx_index: None,
+            },
+            AccountStatus {
+                client_id: 2,
+                available: Amount::from("0.1000"),
+                held: Amount::from("0.0000"),
+                locked: true,
+                tx_count: 1,
+                disputed: vec![],
+                last_tx_index: None,
+            },
+        ];
+        let mut out: Vec<u8> = vec![];
+        write_table_report(&accounts, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        // Every line is the same width, and the wide and the narrow amount
+        // both end at the same column
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+        let column_of = |line: &str, cell: &str| line.find(cell).unwrap() + cell.len();
+        assert_eq!(
+            column_of(lines[0], "available"),
+            column_of(lines[1], "12345.6789")
+        );
+        assert_eq!(
+            column_of(lines[1], "12345.6789"),
+            column_of(lines[2], "0.1000")
+        );
+    }
+
+    #[test]
+    fn jsonl_report_emits_one_standalone_object_per_line() {
+        let accounts = vec![
+            AccountStatus {
+                client_id: 1,
+                available: Amount::from("1.5"),
+                held: Amount::from("0.25"),
+                locked: false,
+                tx_count: 2,
+                disputed: vec![],
+                last_tx_index: None,
+            },
+            AccountStatus {
+                client_id: 2,
+                available: Amount::from("3.0"),
+                held: Amount::default(),
+                locked: true,
+                tx_count: 1,
+                disputed: vec![],
+                last_tx_index: None,
+            },
+        ];
+        let mut out: Vec<u8> = vec![];
+        write_jsonl_report(&accounts, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        // Every line is a complete JSON document on its own
+        for line in &lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(value.is_object());
+        }
+        let first: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["client"], 1);
+        assert_eq!(first["total"], "1.7500");
+    }
+
+    #[test]
+    fn json_report_has_the_expected_shape() {
+        let accounts = vec![AccountStatus {
+            client_id: 1,
+            available: Amount::from("1.5"),
+            held: Amount::from("0.25"),
+            locked: true,
+            tx_count: 2,
+            disputed: vec![],
+            last_tx_index: None,
+        }];
+        let mut out: Vec<u8> = vec![];
+        write_json_report(&accounts, &mut out).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([{
+                "client": 1,
+                "available": "1.5000",
+                "held": "0.2500",
+                "total": "1.7500",
+                "locked": true,
+                "tx_count": 2,
+            }])
+        );
+    }
+}
@@ -0,0 +1,511 @@
+use csv::StringRecord;
+
+use crate::amount::{Amount, RoundingMode};
+
+/// The kind of operation a CSV row describes
+pub enum TransactionType {
+    Deposit,
+    Withdraw,
+    /// Moves funds between two clients atomically. Transfer rows reuse the
+    /// `tx` column for the destination client, so they mint no transaction
+    /// ID of their own and cannot be disputed
+    Transfer,
+    Dispute,
+    Resolve,
+    Chargeback,
+    /// Clears the locked flag a chargeback left behind. An administrative
+    /// operation issued after manual review; unlock rows carry no amount
+    Unlock,
+    Invalid,
+}
+
+impl TransactionType {
+    /// The canonical input spelling of the variant, the inverse of
+    /// `From<&str>`; useful for logging and writers
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionType::Deposit => "deposit",
+            TransactionType::Withdraw => "withdrawal",
+            TransactionType::Transfer => "transfer",
+            TransactionType::Dispute => "dispute",
+            TransactionType::Resolve => "resolve",
+            TransactionType::Chargeback => "chargeback",
+            TransactionType::Unlock => "unlock",
+            TransactionType::Invalid => "invalid",
+        }
+    }
+}
+
+impl From<&str> for TransactionType {
+    /// Lenient conversion used by row parsing: unknown spellings become
+    /// [`TransactionType::Invalid`] so processing can report them per row.
+    /// Use `str::parse` to surface the failure instead
+    fn from(value: &str) -> Self {
+        value.parse().unwrap_or(TransactionType::Invalid)
+    }
+}
+
+/// Error returned when a string names no known transaction type
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownTransactionType(pub String);
+
+impl std::fmt::Display for UnknownTransactionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown transaction type '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownTransactionType {}
+
+impl std::str::FromStr for TransactionType {
+    type Err = UnknownTransactionType;
+
+    /// The fallible counterpart of `From<&str>`; unknown spellings (including
+    /// "invalid", which is only ever an output marker) are errors
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "deposit" => Ok(TransactionType::Deposit),
+            "withdrawal" => Ok(TransactionType::Withdraw),
+            "transfer" => Ok(TransactionType::Transfer),
+            "dispute" => Ok(TransactionType::Dispute),
+            "resolve" => Ok(TransactionType::Resolve),
+            "chargeback" => Ok(TransactionType::Chargeback),
+            "unlock" => Ok(TransactionType::Unlock),
+            other => Err(UnknownTransactionType(other.to_string())),
+        }
+    }
+}
+
+/// A single parsed input row. Dispute-type rows carry no amount of their own
+/// and reference another transaction through `tr_id`
+pub struct Transaction {
+    pub(crate) tr_type: TransactionType,
+    pub(crate) client_id: u16,
+    pub(crate) tr_id: u32,
+    pub(crate) amount: Option<Amount>,
+}
+
+/// Why a CSV row could not be turned into a `Transaction`
+#[derive(Debug, PartialEq, Eq)]
+pub struct RowError {
+    /// Name of the column that was missing or malformed
+    pub field: &'static str,
+    /// 1-based line number of the offending record, when the reader tracked it
+    pub line: Option<u64>,
+}
+
+impl std::fmt::Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(
+                f,
+                "Row at line {}: missing or malformed '{}' field",
+                line, self.field
+            ),
+            None => write!(f, "Row has a missing or malformed '{}' field", self.field),
+        }
+    }
+}
+
+impl std::error::Error for RowError {}
+
+/// Maps the `type`, `client`, `tx` and `amount` columns to their positions in
+/// the input, so files with reordered or extra columns still parse
+pub struct ColumnMap {
+    type_idx: usize,
+    client_idx: usize,
+    tx_idx: usize,
+    amount_idx: usize,
+}
+
+impl ColumnMap {
+    /// Builds a map from a header row. Returns `None` when any of the four
+    /// expected column names is absent, in which case callers should fall
+    /// back to positional parsing. A UTF-8 byte order mark on the first
+    /// header is stripped, since spreadsheet exports often prepend one
+    pub fn from_headers(headers: &StringRecord) -> Option<ColumnMap> {
+        let mut type_idx = None;
+        let mut client_idx = None;
+        let mut tx_idx = None;
+        let mut amount_idx = None;
+        for (i, name) in headers.iter().enumerate() {
+            match name.trim_start_matches('\u{feff}').trim() {
+                "type" => type_idx = Some(i),
+                "client" => client_idx = Some(i),
+                "tx" => tx_idx = Some(i),
+                "amount" => amount_idx = Some(i),
+                _ => {}
+            }
+        }
+        Some(ColumnMap {
+            type_idx: type_idx?,
+            client_idx: client_idx?,
+            tx_idx: tx_idx?,
+            amount_idx: amount_idx?,
+        })
+    }
+
+    /// The classic `type,client,tx,amount` column order
+    pub fn positional() -> ColumnMap {
+        ColumnMap {
+            type_idx: 0,
+            client_idx: 1,
+            tx_idx: 2,
+            amount_idx: 3,
+        }
+    }
+}
+
+impl Transaction {
+    /// Builds a transaction directly, for embedders that already hold
+    /// structured data and have no CSV to parse. Dispute-type rows carry
+    /// `None` for the amount
+    pub fn new(
+        tr_type: TransactionType,
+        client_id: u16,
+        tr_id: u32,
+        amount: Option<Amount>,
+    ) -> Transaction {
+        Transaction {
+            tr_type,
+            client_id,
+            tr_id,
+            amount,
+        }
+    }
+
+    /// Parses a record using the given column layout. Fields are trimmed so
+    /// inputs like `deposit, 1, 1, 1.0` parse the same as their
+    /// tightly-packed form. Amounts round half-up past the fourth decimal;
+    /// use [`Transaction::from_record_rounded`] to choose a different mode
+    pub fn from_record(rec: &StringRecord, columns: &ColumnMap) -> Result<Transaction, RowError> {
+        Transaction::from_record_rounded(rec, columns, RoundingMode::default())
+    }
+
+    /// Like [`Transaction::from_record`], but folds amount digits beyond the
+    /// fourth decimal in per `rounding`
+    pub fn from_record_rounded(
+        rec: &StringRecord,
+        columns: &ColumnMap,
+        rounding: RoundingMode,
+    ) -> Result<Transaction, RowError> {
+        let line = rec.position().map(|p| p.line());
+        let tr_type =
+            TransactionType::from(rec.get(columns.type_idx).map(str::trim).ok_or(RowError {
+                field: "type",
+                line,
+            })?);
+        let client_id = rec
+            .get(columns.client_idx)
+            .and_then(|field| field.trim().parse::<u16>().ok())
+            .ok_or(RowError {
+                field: "client",
+                line,
+            })?;
+        let tr_id = rec
+            .get(columns.tx_idx)
+            .and_then(|field| field.trim().parse::<u32>().ok())
+            .ok_or(RowError { field: "tx", line })?;
+        // An absent or empty amount column is legitimate (dispute-type
+        // rows), but a present, non-empty field that fails to parse is a
+        // row error — it must not collapse to a zero amount
+        let amount = match rec
+            .get(columns.amount_idx)
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+        {
+            Some(field) => {
+                Some(
+                    Amount::try_from_rounded(field, rounding).map_err(|_| RowError {
+                        field: "amount",
+                        line,
+                    })?,
+                )
+            }
+            None => None,
+        };
+        Ok(Transaction {
+            tr_type,
+            client_id,
+            tr_id,
+            amount,
+        })
+    }
+
+    /// The kind of operation the row describes; lets callers outside the
+    /// crate react to e.g. unrecognized types without the fields being public
+    pub fn transaction_type(&self) -> &TransactionType {
+        &self.tr_type
+    }
+}
+
+/// Scans CSV sources for structural problems — malformed fields, unknown
+/// transaction types, fund rows without an amount, reused transaction IDs —
+/// without computing any balances. Backs the binary's `--validate` dry run.
+/// The duplicate-ID check spans every reader fed in, matching how processing
+/// treats multiple files as one continuous stream
+pub struct Validator {
+    seen_fund_ids: std::collections::HashSet<(u16, u32)>,
+    findings: Vec<String>,
+}
+
+impl Validator {
+    pub fn new() -> Validator {
+        Validator {
+            seen_fund_ids: std::collections::HashSet::new(),
+            findings: vec![],
+        }
+    }
+
+    /// Scans every record from an already-configured reader, so the caller's
+    /// delimiter choice carries over
+    pub fn check_reader<R: std::io::Read>(&mut self, mut reader: csv::Reader<R>) {
+        let columns = reader
+            .headers()
+            .ok()
+            .and_then(ColumnMap::from_headers)
+            .unwrap_or_else(ColumnMap::positional);
+        for record in reader.into_records().flatten() {
+            let line = record.position().map(|p| p.line());
+            let at = line.map(|l| format!(" at line {}", l)).unwrap_or_default();
+            match Transaction::from_record(&record, &columns) {
+                Err(err) => self.findings.push(err.to_string()),
+                Ok(tr) => match tr.tr_type {
+                    TransactionType::Invalid => self
+                        .findings
+                        .push(format!("Row{}: unrecognized transaction type", at)),
+                    // Transfers reuse the `tx` column for the destination
+                    // client, so they need an amount but mint no ID
+                    TransactionType::Transfer if tr.amount.is_none() => {
+                        self.findings
+                            .push(format!("Row{}: transfer carries no amount", at));
+                    }
+                    TransactionType::Deposit | TransactionType::Withdraw => {
+                        if tr.amount.is_none() {
+                            self.findings.push(format!(
+                                "Row{}: {} carries no amount",
+                                at,
+                                tr.tr_type.as_str()
+                            ));
+                        }
+                        // IDs are scoped per client, so two clients sharing
+                        // a coincidental ID is not a finding
+                        if !self.seen_fund_ids.insert((tr.client_id, tr.tr_id)) {
+                            self.findings
+                                .push(format!("Row{}: transaction ID {} is reused", at, tr.tr_id));
+                        }
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    /// Every problem found so far, in input order
+    pub fn findings(&self) -> &[String] {
+        &self.findings
+    }
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Validator::new()
+    }
+}
+
+impl TryFrom<StringRecord> for Transaction {
+    type Error = RowError;
+
+    fn try_from(rec: StringRecord) -> Result<Self, Self::Error> {
+        Transaction::from_record(&rec, &ColumnMap::positional())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transaction_type_round_trips_through_as_str() {
+        let variants = [
+            TransactionType::Deposit,
+            TransactionType::Withdraw,
+            TransactionType::Transfer,
+            TransactionType::Dispute,
+            TransactionType::Resolve,
+            TransactionType::Chargeback,
+            TransactionType::Unlock,
+        ];
+        for variant in variants {
+            let round_tripped = TransactionType::from(variant.as_str());
+            assert_eq!(round_tripped.as_str(), variant.as_str());
+        }
+        assert_eq!(TransactionType::Invalid.as_str(), "invalid");
+    }
+
+    #[test]
+    fn fields_are_trimmed_before_parsing() {
+        let padded = StringRecord::from(vec![" deposit ", " 1 ", " 2 ", " 1.5 "]);
+        let tight = StringRecord::from(vec!["deposit", "1", "2", "1.5"]);
+        let padded = Transaction::try_from(padded).unwrap();
+        let tight = Transaction::try_from(tight).unwrap();
+        assert!(matches!(padded.tr_type, TransactionType::Deposit));
+        assert_eq!(padded.client_id, tight.client_id);
+        assert_eq!(padded.tr_id, tight.tr_id);
+        assert_eq!(padded.amount, tight.amount);
+    }
+
+    #[test]
+    fn short_row_reports_the_missing_field() {
+        let rec = StringRecord::from(vec!["deposit", "1"]);
+        assert_eq!(
+            Transaction::try_from(rec).err(),
+            Some(RowError {
+                field: "tx",
+                line: None
+            })
+        );
+    }
+
+    #[test]
+    fn reordered_columns_are_located_by_name() {
+        let headers = StringRecord::from(vec!["amount", "tx", "client", "type"]);
+        let columns = ColumnMap::from_headers(&headers).unwrap();
+        let rec = StringRecord::from(vec!["1.5", "2", "1", "deposit"]);
+        let transaction = Transaction::from_record(&rec, &columns).unwrap();
+        assert!(matches!(transaction.tr_type, TransactionType::Deposit));
+        assert_eq!(transaction.client_id, 1);
+        assert_eq!(transaction.tr_id, 2);
+        assert_eq!(transaction.amount, Some(Amount::from("1.5")));
+    }
+
+    #[test]
+    fn extra_trailing_column_is_ignored() {
+        let headers = StringRecord::from(vec!["type", "client", "tx", "amount", "note"]);
+        let columns = ColumnMap::from_headers(&headers).unwrap();
+        let rec = StringRecord::from(vec!["deposit", "1", "2", "1.5", "rent"]);
+        let transaction = Transaction::from_record(&rec, &columns).unwrap();
+        assert_eq!(transaction.amount, Some(Amount::from("1.5")));
+    }
+
+    #[test]
+    fn byte_order_mark_on_the_first_header_is_ignored() {
+        let headers = StringRecord::from(vec!["\u{feff}type", "client", "tx", "amount"]);
+        let columns = ColumnMap::from_headers(&headers).unwrap();
+        let rec = StringRecord::from(vec!["deposit", "1", "2", "1.5"]);
+        let transaction = Transaction::from_record(&rec, &columns).unwrap();
+        assert!(matches!(transaction.tr_type, TransactionType::Deposit));
+    }
+
+    #[test]
+    fn unrecognized_headers_have_no_column_map() {
+        let headers = StringRecord::from(vec!["a", "b", "c", "d"]);
+        assert!(ColumnMap::from_headers(&headers).is_none());
+    }
+
+    #[test]
+    fn validator_reports_each_kind_of_problem() {
+        let input: &[u8] = b"type,client,tx,amount\n\
+            deposit,1,1,1.0\n\
+            teleport,1,2,1.0\n\
+            deposit,abc,3,1.0\n\
+            withdrawal,1,4,\n\
+            deposit,1,1,2.0\n";
+        let mut validator = Validator::new();
+        validator.check_reader(csv::Reader::from_reader(input));
+        let findings = validator.findings();
+        assert_eq!(findings.len(), 4);
+        assert!(findings[0].contains("unrecognized transaction type"));
+        assert!(findings[1].contains("'client'"));
+        assert!(findings[2].contains("carries no amount"));
+        assert!(findings[3].contains("transaction ID 1 is reused"));
+    }
+
+    #[test]
+    fn validator_flags_a_non_numeric_amount() {
+        let input: &[u8] = b"type,client,tx,amount\ndeposit,1,1,abc\n";
+        let mut validator = Validator::new();
+        validator.check_reader(csv::Reader::from_reader(input));
+        let findings = validator.findings();
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].contains("'amount'"));
+    }
+
+    #[test]
+    fn validator_is_quiet_on_clean_input() {
+        let input: &[u8] = b"type,client,tx,amount\ndeposit,1,1,1.0\ndispute,1,1,\n";
+        let mut validator = Validator::new();
+        validator.check_reader(csv::Reader::from_reader(input));
+        assert!(validator.findings().is_empty());
+    }
+
+    #[test]
+    fn non_numeric_amount_is_an_error() {
+        // A garbage amount must not collapse to a zero deposit; the whole
+        // row is rejected so strict mode and the validator can surface it
+        let rec = StringRecord::from(vec!["deposit", "1", "1", "abc"]);
+        assert_eq!(
+            Transaction::try_from(rec).err(),
+            Some(RowError {
+                field: "amount",
+                line: None
+            })
+        );
+    }
+
+    #[test]
+    fn non_numeric_client_id_is_an_error() {
+        let rec = StringRecord::from(vec!["deposit", "abc", "1", "1.0"]);
+        assert_eq!(
+            Transaction::try_from(rec).err(),
+            Some(RowError {
+                field: "client",
+                line: None
+            })
+        );
+    }
+
+    #[test]
+    fn transaction_types_parse_fallibly() {
+        assert!(matches!(
+            "deposit".parse::<TransactionType>(),
+            Ok(TransactionType::Deposit)
+        ));
+        assert!(matches!(
+            "chargeback".parse::<TransactionType>(),
+            Ok(TransactionType::Chargeback)
+        ));
+        assert!(matches!(
+            "teleport".parse::<TransactionType>(),
+            Err(UnknownTransactionType(name)) if name == "teleport"
+        ));
+        // The lenient conversion still folds unknown spellings to Invalid
+        assert!(matches!(
+            TransactionType::from("teleport"),
+            TransactionType::Invalid
+        ));
+    }
+
+    #[test]
+    fn non_numeric_transaction_id_is_an_error() {
+        // A malformed ID must not default to 0, where it could collide with
+        // a legitimate transaction 0 and break dispute lookups
+        let rec = StringRecord::from(vec!["deposit", "1", "oops", "1.0"]);
+        assert_eq!(
+            Transaction::try_from(rec).err(),
+            Some(RowError {
+                field: "tx",
+                line: None
+            })
+        );
+    }
+
+    #[test]
+    fn client_id_zero_is_valid() {
+        // 0 parses fine as a u16, so only genuine parse failures are
+        // rejected; a garbage client field must not fall back to client 0
+        let tr =
+            Transaction::try_from(StringRecord::from(vec!["deposit", "0", "1", "1.0"])).unwrap();
+        assert_eq!(tr.client_id, 0);
+    }
+}
@@ -0,0 +1,494 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn reads_semicolon_separated_input() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--delimiter", ";", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type;client;tx;amount\ndeposit;1;1;2.5\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1,2.5000,0.0000,2.5000,false"));
+}
+
+#[test]
+fn precision_flag_rounds_report_amounts() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--precision", "2", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,1.005\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1,1.01,0.00,1.01,false"));
+}
+
+#[test]
+fn multiple_files_form_one_transaction_stream() {
+    let dir = std::env::temp_dir();
+    let first = dir.join("csv_payment_processor_shard_1.csv");
+    let second = dir.join("csv_payment_processor_shard_2.csv");
+    std::fs::write(&first, "type,client,tx,amount\ndeposit,1,1,10.0\n").unwrap();
+    // The dispute in the second shard references the deposit in the first
+    std::fs::write(&second, "type,client,tx,amount\ndispute,1,1,\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args([&first, &second])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1,0.0000,10.0000,10.0000,false"));
+    std::fs::remove_file(first).ok();
+    std::fs::remove_file(second).ok();
+}
+
+#[test]
+fn client_filter_prints_only_the_selected_accounts() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--client", "2", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(
+            b"type,client,tx,amount\n\
+              deposit,1,1,1.0\n\
+              deposit,2,2,2.0\n\
+              deposit,3,3,3.0\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some("client,available,held,total,locked,tx_count")
+    );
+    assert_eq!(lines.next(), Some("2,2.0000,0.0000,2.0000,false,1"));
+    assert_eq!(lines.next(), None);
+}
+
+#[test]
+fn rounding_flag_controls_extra_fractional_digits() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--rounding", "truncate", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,1.00005\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1,1.0000,0.0000,1.0000,false"));
+}
+
+#[test]
+fn unreadable_input_fails_the_run() {
+    let output = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .arg("/no/such/transactions.csv")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("/no/such/transactions.csv"));
+}
+
+#[test]
+fn validate_flag_reports_problems_without_balances() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--validate", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\nteleport,1,1,1.0\ndeposit,abc,2,1.0\nwithdrawal,1,3,\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr.lines().count(), 3);
+}
+
+#[test]
+fn validate_flag_passes_clean_input() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--validate", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,1.0\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn reads_csv_from_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,5.0\nwithdrawal,1,2,1.5\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("client,available,held,total,locked,tx_count\n"));
+    assert!(stdout.contains("1,3.5000,0.0000,3.5000,false"));
+}
+
+#[test]
+fn strict_mode_fails_on_an_unknown_transaction_type() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--strict", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(
+            b"type,client,tx,amount\n\
+              deposit,1,1,5.0\n\
+              teleport,1,2,1.0\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Row at line 3: unrecognized transaction type"));
+    // No balances are reported once strict mode has rejected the stream
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn unknown_types_are_skipped_without_the_strict_flag() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(
+            b"type,client,tx,amount\n\
+              deposit,1,1,5.0\n\
+              teleport,1,2,1.0\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1,5.0000,0.0000,5.0000,false"));
+}
+
+#[test]
+fn currency_scale_flag_reports_cents() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--currency-scale", "2", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,12.3456\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1,12.35,0.00,12.35,false"));
+}
+
+#[test]
+fn output_flag_writes_the_report_to_a_file() {
+    let path = std::env::temp_dir().join("csv_payment_processor_output.csv");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--output", path.to_str().unwrap(), "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,2.5\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    // The report lands in the file, leaving stdout empty
+    assert!(output.stdout.is_empty());
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains("1,2.5000,0.0000,2.5000,false"));
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn header_only_input_prints_just_the_report_header() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, "client,available,held,total,locked,tx_count\n");
+}
+
+#[test]
+fn completely_empty_input_prints_just_the_report_header() {
+    let child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    // stdin is closed immediately with nothing written
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, "client,available,held,total,locked,tx_count\n");
+}
+
+#[test]
+fn directory_input_processes_its_csv_files_in_sorted_order() {
+    let dir = std::env::temp_dir().join("csv_payment_processor_archive");
+    std::fs::create_dir_all(&dir).unwrap();
+    // The dispute in 02.csv references the deposit in 01.csv, so it only
+    // settles when the files are processed in filename order
+    std::fs::write(
+        dir.join("01.csv"),
+        "type,client,tx,amount\ndeposit,1,1,10.0\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("02.csv"), "type,client,tx,amount\ndispute,1,1,\n").unwrap();
+    std::fs::write(dir.join("notes.txt"), "not a csv\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1,0.0000,10.0000,10.0000,false"));
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn no_header_flag_processes_the_first_row_as_data() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--no-header", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(
+            b"deposit,1,1,2.0\n\
+              deposit,1,2,3.0\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // Both rows count; a header-consuming reader would only see the second
+    assert!(stdout.contains("1,5.0000,0.0000,5.0000,false,2"));
+}
+
+#[test]
+fn trace_flag_prints_per_row_balances_on_stderr() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--trace", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,5.0\nwithdrawal,1,2,1.5\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("0,deposit,1,1,5.0000,5.0000,0.0000,false"));
+    assert!(stderr.contains("1,withdrawal,1,2,1.5000,3.5000,0.0000,false"));
+    // The report itself stays on stdout, untouched by the trace
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1,3.5000,0.0000,3.5000,false"));
+}
+
+#[test]
+fn malformed_mid_file_record_does_not_stop_processing() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    // The middle row has too few fields, so the csv reader errors on it
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,5.0\ndeposit,2\ndeposit,1,2,1.0\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // Rows on both sides of the corrupt record still apply
+    assert!(stdout.contains("1,6.0000,0.0000,6.0000,false"));
+}
+
+#[test]
+fn strict_mode_aborts_on_a_malformed_record() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--strict", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"type,client,tx,amount\ndeposit,1,1,5.0\ndeposit,2\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Unreadable record"));
+}
+
+#[test]
+fn input_path_falls_back_to_the_environment_variable() {
+    let dir = std::env::temp_dir();
+    let path = dir.join("csv_payment_processor_env_input.csv");
+    std::fs::write(&path, "type,client,tx,amount\ndeposit,1,1,4.0\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .env("CSV_INPUT_PATH", &path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1,4.0000,0.0000,4.0000,false"));
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn explicit_path_argument_outranks_the_environment_variable() {
+    let dir = std::env::temp_dir();
+    let env_file = dir.join("csv_payment_processor_env_loser.csv");
+    let arg_file = dir.join("csv_payment_processor_arg_winner.csv");
+    std::fs::write(&env_file, "type,client,tx,amount\ndeposit,1,1,1.0\n").unwrap();
+    std::fs::write(&arg_file, "type,client,tx,amount\ndeposit,2,2,9.0\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .env("CSV_INPUT_PATH", &env_file)
+        .arg(&arg_file)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("2,9.0000,0.0000,9.0000,false"));
+    assert!(!stdout.contains("1,1.0000"));
+    std::fs::remove_file(env_file).ok();
+    std::fs::remove_file(arg_file).ok();
+}
+
+#[test]
+fn progress_lines_go_to_stderr_not_stdout() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_csv_payment_processor"))
+        .args(["--progress-every", "2", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("binary should start");
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(
+            b"type,client,tx,amount\n\
+            deposit,1,1,1.0\n\
+            deposit,1,2,1.0\n\
+            deposit,1,3,1.0\n\
+            deposit,1,4,1.0\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Processed 2 records"));
+    assert!(stderr.contains("Processed 4 records"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("Processed"));
+    assert!(stdout.contains("1,4.0000,0.0000,4.0000,false"));
+}
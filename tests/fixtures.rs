@@ -0,0 +1,75 @@
+use csv_payment_processor::{process_reader, AccountStatus, Amount, ProcessError};
+
+/// Runs the processing pipeline over one fixture CSV from `tests/fixtures/`
+fn process_fixture(name: &str) -> (Vec<AccountStatus>, Vec<ProcessError>) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    let file = std::fs::File::open(&path)
+        .unwrap_or_else(|err| panic!("fixture {} should open: {}", path.display(), err));
+    process_reader(file)
+}
+
+#[test]
+fn basic_deposits_accumulate_per_client() {
+    let (statuses, errors) = process_fixture("basic_deposits.csv");
+    assert!(errors.is_empty());
+    assert_eq!(statuses.len(), 2);
+    assert_eq!(statuses[0].client_id, 1);
+    assert_eq!(statuses[0].available, Amount::from("12.5"));
+    assert_eq!(statuses[0].tx_count, 2);
+    assert_eq!(statuses[1].client_id, 2);
+    assert_eq!(statuses[1].available, Amount::from("5.5"));
+    assert_eq!(statuses[1].tx_count, 1);
+}
+
+#[test]
+fn withdrawal_applies_only_when_covered() {
+    let (statuses, errors) = process_fixture("withdrawal.csv");
+    assert!(errors.is_empty());
+    assert_eq!(statuses.len(), 1);
+    // The 4.0 withdrawal applies; the 100.0 one exceeds the balance and is
+    // skipped, though it still counts toward tx_count
+    assert_eq!(statuses[0].available, Amount::from("6.0"));
+    assert_eq!(statuses[0].tx_count, 3);
+    assert!(!statuses[0].locked);
+}
+
+#[test]
+fn dispute_then_resolve_restores_the_balance() {
+    let (statuses, errors) = process_fixture("dispute_resolve.csv");
+    assert!(errors.is_empty());
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(statuses[0].available, Amount::from("10.0"));
+    assert_eq!(statuses[0].held, Amount::from("0"));
+    assert!(!statuses[0].locked);
+    assert!(statuses[0].disputed.is_empty());
+}
+
+#[test]
+fn dispute_then_chargeback_locks_the_account() {
+    let (statuses, errors) = process_fixture("dispute_chargeback.csv");
+    assert!(errors.is_empty());
+    assert_eq!(statuses.len(), 1);
+    // The disputed 10.0 deposit is clawed back; the deposit after the lock
+    // is refused, leaving only the untouched 3.0
+    assert_eq!(statuses[0].available, Amount::from("3.0"));
+    assert_eq!(statuses[0].held, Amount::from("0"));
+    assert!(statuses[0].locked);
+    assert_eq!(statuses[0].tx_count, 2);
+}
+
+#[test]
+fn mixed_clients_settle_transfers_and_disputes_independently() {
+    let (statuses, errors) = process_fixture("mixed_clients.csv");
+    assert!(errors.is_empty());
+    assert_eq!(statuses.len(), 2);
+    // Client 1 transferred 4.0 to client 2, who then withdrew 6.0 and saw
+    // their own 20.0 deposit disputed
+    assert_eq!(statuses[0].client_id, 1);
+    assert_eq!(statuses[0].available, Amount::from("6.0"));
+    assert_eq!(statuses[1].client_id, 2);
+    assert_eq!(statuses[1].available, Amount::from("-2.0"));
+    assert_eq!(statuses[1].held, Amount::from("20.0"));
+    assert_eq!(statuses[1].disputed, vec![2]);
+}